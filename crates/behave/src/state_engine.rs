@@ -10,6 +10,20 @@ pub struct TransitionStore<T> {
     target: State<T>,
 }
 
+impl<T> TransitionStore<T> {
+    pub fn sources(&self) -> &[State<T>] {
+        &self.sources
+    }
+
+    pub fn target(&self) -> State<T> {
+        self.target
+    }
+
+    pub fn has_source(&self, state: State<T>) -> bool {
+        self.sources.contains(&state)
+    }
+}
+
 #[derive(Debug, Resource)]
 pub struct StateEngine<T> {
     transitions: HashMap<Transition<T>, TransitionStore<T>>,
@@ -67,6 +81,60 @@ impl<T> StateEngine<T> {
     pub fn get_transition(&self, id: impl Into<Transition<T>>) -> Option<&TransitionStore<T>> {
         self.transitions.get(&id.into())
     }
+
+    pub fn iter_transitions(&self) -> impl Iterator<Item = (Transition<T>, &TransitionStore<T>)> {
+        self.transitions.iter().map(|(&id, store)| (id, store))
+    }
+
+    pub fn iter_transitions_from(&self, state: impl Into<State<T>>) -> impl Iterator<Item = (Transition<T>, &TransitionStore<T>)> {
+        let state = state.into();
+        self.iter_transitions().filter(move |(_, store)| store.has_source(state))
+    }
+
+    pub fn iter_transitions_into(&self, state: impl Into<State<T>>) -> impl Iterator<Item = (Transition<T>, &TransitionStore<T>)> {
+        let state = state.into();
+        self.iter_transitions().filter(move |(_, store)| store.target == state)
+    }
+}
+
+// NOTE Editing the graph never moves entities. An entity sitting in a state
+//      whose outgoing transitions were removed stays there until a transition
+//      out of it is added again, or it's moved with `force_transition`. Any
+//      pending triggers for removed transitions are ignored when applied.
+impl<T> StateEngine<T> {
+    pub fn remove_transition(&mut self, id: impl Into<Transition<T>>) -> Option<TransitionStore<T>> {
+        self.transitions.remove(&id.into())
+    }
+
+    // NOTE Only replaces a transition that's already there, use add_transition
+    //      for new ones. Returns what was replaced.
+    pub fn replace_transition(&mut self, id: impl Into<Transition<T>>, target: impl Into<State<T>>, sources: &[State<T>]) -> Option<TransitionStore<T>> {
+        self.transitions.get_mut(&id.into()).map(|store| std::mem::replace(store, TransitionStore{
+            target: target.into(),
+            sources: sources.to_vec(),
+        }))
+    }
+
+    pub fn remove_transition_source(&mut self, id: impl Into<Transition<T>>, source: impl Into<State<T>>) -> bool {
+        let source = source.into();
+        if let Some(store) = self.transitions.get_mut(&id.into()) {
+            let len = store.sources.len();
+            store.sources.retain(|&v| v != source);
+            store.sources.len() != len
+        } else {
+            false
+        }
+    }
+
+    pub fn remove_state(&mut self, state: impl Into<State<T>>) -> usize {
+        let state = state.into();
+        let len = self.transitions.len();
+        self.transitions.retain(|_, store| store.target != state);
+        for store in self.transitions.values_mut() {
+            store.sources.retain(|&v| v != state);
+        }
+        len - self.transitions.len()
+    }
 }
 
 impl<T> StateEngine<T> {
@@ -92,4 +160,112 @@ impl<T> StateEngine<T> {
         self.by_leaving.clear();
         self.by_entered.clear();
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::Entity;
+    use crate::prelude::{StateEngine, StateMachine};
+
+    #[derive(Debug)]
+    pub struct Marker;
+
+    crate::behave_define!(
+        Marker,
+        STATE_A,
+        STATE_B,
+        STATE_C,
+        (ACT_GO,   STATE_B, [STATE_A]),
+        (ACT_NEXT, STATE_C, [STATE_B]),
+        (ACT_BACK, STATE_A, [STATE_B, STATE_C]),
+    );
+
+    fn get_engine() -> StateEngine<Marker> {
+        let mut engine = StateEngine::default();
+        for record in [&ACT_GO, &ACT_NEXT, &ACT_BACK] {
+            assert!(engine.add_transition(record, record.target, record.sources));
+        }
+        engine
+    }
+
+    #[test]
+    fn check_edit_transitions() {
+        let mut engine = get_engine();
+
+        // Removed and added back
+        let removed = engine.remove_transition(&ACT_GO).unwrap();
+        assert_eq!(removed.target(), STATE_B);
+        assert!(engine.get_transition(&ACT_GO).is_none());
+        assert!(engine.remove_transition(&ACT_GO).is_none());
+        assert!(engine.add_transition(&ACT_GO, STATE_C, &[STATE_A]));
+        assert_eq!(engine.get_transition(&ACT_GO).unwrap().target(), STATE_C);
+        assert!(!engine.add_transition(&ACT_GO, STATE_B, &[STATE_A]));
+
+        // Replacing only works on what's there
+        let replaced = engine.replace_transition(&ACT_GO, STATE_B, &[STATE_C]).unwrap();
+        assert_eq!(replaced.target(), STATE_C);
+        assert_eq!(engine.get_transition(&ACT_GO).unwrap().sources(), &[STATE_C]);
+        engine.remove_transition(&ACT_GO);
+        assert!(engine.replace_transition(&ACT_GO, STATE_B, &[STATE_A]).is_none());
+        assert!(engine.get_transition(&ACT_GO).is_none());
+
+        assert!( engine.remove_transition_source(&ACT_BACK, STATE_B));
+        assert!(!engine.remove_transition_source(&ACT_BACK, STATE_B));
+        assert!(!engine.remove_transition_source(&ACT_GO,   STATE_A));
+        assert_eq!(engine.get_transition(&ACT_BACK).unwrap().sources(), &[STATE_C]);
+    }
+
+    #[test]
+    fn check_iter_transitions() {
+        let engine = get_engine();
+        assert_eq!(engine.iter_transitions().count(), 3);
+
+        let mut from: Vec<_> = engine.iter_transitions_from(STATE_B).map(|(id, _)| id).collect();
+        from.sort_by_key(|v| v.to_str());
+        assert_eq!(from, [ACT_BACK.id, ACT_NEXT.id]);
+
+        let into: Vec<_> = engine.iter_transitions_into(STATE_A).map(|(id, _)| id).collect();
+        assert_eq!(into, [ACT_BACK.id]);
+        assert_eq!(engine.iter_transitions_from(STATE_A).count(), 1);
+        assert_eq!(engine.iter_transitions_into(STATE_C).count(), 1);
+    }
+
+    #[test]
+    fn check_remove_state() {
+        let mut engine = get_engine();
+
+        // Drops the transitions into it, and it as a source for the rest
+        assert_eq!(engine.remove_state(STATE_B), 1);
+        assert!(engine.get_transition(&ACT_GO).is_none());
+        assert!(engine.get_transition(&ACT_NEXT).unwrap().sources().is_empty());
+        assert_eq!(engine.get_transition(&ACT_BACK).unwrap().sources(), &[STATE_C]);
+        assert_eq!(engine.iter_transitions_from(STATE_B).count(), 0);
+        assert_eq!(engine.remove_state(STATE_B), 0);
+    }
+
+    #[test]
+    fn check_apply_after_remove() {
+        let mut engine = get_engine();
+        let entity = Entity::from_raw(0);
+        let mut machine = StateMachine::new(STATE_A);
+
+        // The pending trigger is ignored, and the entity stays where it is
+        machine.trigger(&ACT_GO);
+        engine.remove_transition(&ACT_GO);
+        assert!(!engine.apply_transition(entity, &mut machine));
+        assert!(machine.is(STATE_A));
+        assert_eq!(engine.get_current(STATE_A), Some(&[entity][..]));
+        assert!(engine.get_entering(STATE_B).is_none());
+
+        // Until it can leave again
+        engine.clear();
+        engine.add_transition(&ACT_GO, STATE_B, &[STATE_A]);
+        machine.trigger(&ACT_GO);
+        assert!(engine.apply_transition(entity, &mut machine));
+        assert!(machine.is(STATE_B));
+        assert_eq!(machine.last(), (STATE_A, ACT_GO.id));
+        assert_eq!(engine.get_leaving(STATE_A),  Some(&[entity][..]));
+        assert_eq!(engine.get_entering(STATE_B), Some(&[entity][..]));
+        assert_eq!(engine.get_current(STATE_B),  Some(&[entity][..]));
+    }
+}
@@ -14,6 +14,15 @@ pub struct StateMachine<T: 'static> {
 }
 
 impl<T> StateMachine<T> {
+    pub fn new(state: impl Into<State<T>>) -> Self {
+        let state = state.into();
+        Self{
+            last:    (state, Transition::default()),
+            current: state,
+            next:    Vec::new(),
+        }
+    }
+
     pub fn is(&self, id: State<T>) -> bool {
         self.current == id
     }
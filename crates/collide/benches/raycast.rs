@@ -0,0 +1,93 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use std::time::{Duration, Instant};
+
+use bevy::prelude::Vec2;
+use nvm_collide::prelude::*;
+
+#[path = "../src/test_util.rs"]
+mod test_util;
+use test_util::Rng;
+
+const SHAPE_COUNTS: [usize; 3] = [100, 1_000, 10_000];
+const RAY_COUNT:    usize      = 64;
+const ROUNDS:       usize      = 20;
+
+pub fn main() {
+    println!("{:>8} {:>8} {:>14} {:>14} {:>14} {:>14}", "shapes", "shape", "scalar", "batch", "scalar first", "batch first");
+    for count in SHAPE_COUNTS {
+        let mut rng = Rng(0x2545_f491);
+        let extent  = 2.0*(count as f32).sqrt();
+        let rays: Vec<_> = (0..RAY_COUNT).map(|_| RayCaster::new_segment(rng.point(extent), rng.direction(), 0.0, extent)).collect();
+
+        let rects: Vec<_> = (0..count).map(|_| (rng.point(extent), Vec2::new(rng.range(0.2, 1.0), rng.range(0.2, 1.0)))).collect();
+        let rect_batch: RectBatch = rects.iter().copied().collect();
+        let mut hits = [0, 0];
+        print_row(count, "rect", [
+            time(|| for ray in rays.iter() {
+                hits[0] += rects.iter().filter(|(origin, size)| ray.test_rect(*origin, *size).is_some_and(|v| is_in_range(ray, v))).count();
+            }),
+            time(|| {
+                let mut out = vec![0.0; rect_batch.len()];
+                for ray in rays.iter() {
+                    ray.test_rect_batch(&rect_batch, &mut out);
+                    hits[1] += out.iter().filter(|v| v.is_finite()).count();
+                }
+            }),
+            time(|| for ray in rays.iter() {
+                std::hint::black_box(rects.iter()
+                    .filter_map(|(origin, size)| ray.test_rect(*origin, *size).filter(|v| is_in_range(ray, *v)))
+                    .min_by(|[a, _], [b, _]| a.distance.total_cmp(&b.distance)));
+            }),
+            time(|| for ray in rays.iter() {
+                std::hint::black_box(ray.test_rect_batch_closest(&rect_batch));
+            }),
+        ]);
+        assert_eq!(hits[0], hits[1], "rect batch disagrees with scalar");
+
+        let circles: Vec<_> = (0..count).map(|_| (rng.point(extent), rng.range(0.2, 1.0))).collect();
+        let circle_batch: CircleBatch = circles.iter().copied().collect();
+        let mut hits = [0, 0];
+        print_row(count, "circle", [
+            time(|| for ray in rays.iter() {
+                hits[0] += circles.iter().filter(|(origin, radius)| ray.test_circle(*origin, *radius).is_some_and(|v| is_in_range(ray, v))).count();
+            }),
+            time(|| {
+                let mut out = vec![0.0; circle_batch.len()];
+                for ray in rays.iter() {
+                    ray.test_circle_batch(&circle_batch, &mut out);
+                    hits[1] += out.iter().filter(|v| v.is_finite()).count();
+                }
+            }),
+            time(|| for ray in rays.iter() {
+                std::hint::black_box(circles.iter()
+                    .filter_map(|(origin, radius)| ray.test_circle(*origin, *radius).filter(|v| is_in_range(ray, *v)))
+                    .min_by(|[a, _], [b, _]| a.distance.total_cmp(&b.distance)));
+            }),
+            time(|| for ray in rays.iter() {
+                std::hint::black_box(ray.test_circle_batch_closest(&circle_batch));
+            }),
+        ]);
+        assert_eq!(hits[0], hits[1], "circle batch disagrees with scalar");
+    }
+}
+
+// NOTE The shape tests are unbounded, this is the range check RayCaster::test does
+fn is_in_range(ray: &RayCaster, [enter, exit]: [RayIntersection; 2]) -> bool {
+    enter.distance <= ray.max_distance() && exit.distance >= ray.min_distance()
+}
+
+fn time(mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        f();
+    }
+    start.elapsed() / ROUNDS as u32
+}
+
+fn print_row(count: usize, shape: &str, times: [Duration; 4]) {
+    println!(
+        "{:>8} {:>8} {:>14.3?} {:>14.3?} {:>14.3?} {:>14.3?}",
+        count, shape, times[0], times[1], times[2], times[3]
+    );
+}
@@ -26,7 +26,7 @@ impl Shape {
     }
 
     fn next(&mut self) {
-        let next = (self.1+1) % 23;
+        let next = (self.1+1) % 26;
         self.0 = Self::get_shape_at_index(next);
         self.1 = next;
     }
@@ -55,6 +55,9 @@ impl Shape {
             20 => Box::new(RampBoxyRound::new(Vec2::ZERO, Vec2::new(-2.0, -1.0).normalize(), 200.0, Vec2::new(50.0, 25.0), 25.0)),
             21 => Box::new(RampBoxyRound::new(Vec2::ZERO, Vec2::new(-2.0,  1.0).normalize(), 200.0, Vec2::new(50.0, 25.0), 25.0)),
             22 => Box::new(RampBoxyRound::new(Vec2::ZERO, Vec2::new( 2.0,  1.0).normalize(), 200.0, Vec2::new(50.0, 25.0), 25.0)),
+            23 => Box::new(Capsule::new(Vec2::ZERO, Vec2::new(2.0, 1.0).normalize(), 150.0, 50.0)),
+            24 => Box::new(EdgeChain::new((0..12).map(|i| Vec2::new(150.0 - 25.0*i as f32, 20.0*(i as f32).sin())), None, None)),
+            25 => Box::new(PolygonLarge::new_from_points((0..24).map(|i| Vec2::from_angle(i as f32 * std::f32::consts::TAU/24.0) * if i % 2 == 0 { 150.0 } else { 100.0 }))),
              _ => Box::new(Ball::new(Vec2::ZERO, 50.0)),
         }
     }
@@ -26,7 +26,7 @@ impl Shape {
     }
 
     fn next(&mut self) {
-        let next = (self.1+1) % 13;
+        let next = (self.1+1) % 16;
         self.0 = Self::get_shape_at_index(next);
         self.1 = next;
     }
@@ -45,6 +45,9 @@ impl Shape {
             10 => RampRound::new(Vec2::ZERO, Vec2::new(-2.0, -1.0).normalize(), 200.0, 25.0).into(),
             11 => RampRound::new(Vec2::ZERO, Vec2::new(-2.0,  1.0).normalize(), 200.0, 25.0).into(),
             12 => RampRound::new(Vec2::ZERO, Vec2::new( 2.0,  1.0).normalize(), 200.0, 25.0).into(),
+            13 => Capsule::new(Vec2::ZERO, Vec2::new(2.0, 1.0).normalize(), 150.0, 50.0).into(),
+            14 => PolygonSmall::try_new_from_points((0..6).map(|i| Vec2::from_angle(i as f32 * std::f32::consts::TAU/6.0) * 100.0)).unwrap().into(),
+            15 => PolygonSmallRound::try_new_from_points((0..5).map(|i| Vec2::from_angle(i as f32 * std::f32::consts::TAU/5.0) * 100.0), 25.0).unwrap().into(),
              _ => Ball::new(Vec2::ZERO, 50.0).into(),
         }
     }
@@ -27,6 +27,14 @@ pub fn render_shape(gizmos: &mut Gizmos, shape: &dyn ShapeMarkerTrait, colour: C
                 gizmos.circle_2d(far, 5.0, Color::TEAL);
             }
         },
+        ShapeDebugData::Chain { .. } => {
+            let ShapeDebugData::Chain { points, .. } = &data else { unreachable!() };
+            gizmos.linestrip_2d(points.iter().copied(), colour);
+            for ([from, to, norm], _) in data.iter_segments() {
+                let mid = (from + to)*0.5;
+                gizmos.line_2d(mid, mid + norm*20.0, Color::BLUE);
+            }
+        },
         ShapeDebugData::PolygonRound { radius, .. } => {
             for ([from, to, norm], offset) in data.iter_segments() {
                 let offset = norm * offset;
@@ -3,7 +3,21 @@
 mod shape;
 mod ray;
 
+mod shape3d;
+mod ray3d;
+
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
+
 pub mod prelude {
     pub use crate::ray::*;
     pub use crate::shape::*;
-}
\ No newline at end of file
+}
+
+// NOTE The 3D types mirror the 2D ones, names included, so only one of the
+//      preludes can be glob imported at a time.
+
+pub mod prelude3d {
+    pub use crate::ray3d::*;
+    pub use crate::shape3d::*;
+}
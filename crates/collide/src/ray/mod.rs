@@ -7,4 +7,7 @@ mod ray_intersection;
 pub use ray_intersection::*;
 
 mod ray_target;
-pub use ray_target::*;
\ No newline at end of file
+pub use ray_target::*;
+
+mod ray_batch;
+pub use ray_batch::*;
@@ -0,0 +1,289 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use bevy::math::{BVec4A, Vec2, Vec4};
+
+use super::{RayCaster, RayIntersection};
+
+// NOTE Shapes are stored 4 to a lane, so one ray is tested against 4 shapes
+//      at a time. The last lane can be partly empty, those are masked out.
+
+const LANES: usize = 4;
+
+#[derive(Debug, Clone, Default)]
+pub struct RectBatch {
+    min_x: Vec<Vec4>,
+    min_y: Vec<Vec4>,
+    max_x: Vec<Vec4>,
+    max_y: Vec<Vec4>,
+    len:   usize,
+}
+
+impl RectBatch {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn push(&mut self, origin: Vec2, size: Vec2) {
+        let (lane, idx) = (self.len / LANES, self.len % LANES);
+        if idx == 0 {
+            for v in [&mut self.min_x, &mut self.min_y, &mut self.max_x, &mut self.max_y] {
+                v.push(Vec4::ZERO);
+            }
+        }
+
+        self.min_x[lane][idx] = origin.x - size.x;
+        self.min_y[lane][idx] = origin.y - size.y;
+        self.max_x[lane][idx] = origin.x + size.x;
+        self.max_y[lane][idx] = origin.y + size.y;
+        self.len += 1;
+    }
+
+    pub fn get(&self, index: usize) -> (Vec2, Vec2) {
+        debug_assert!(index < self.len);
+        let (lane, idx) = (index / LANES, index % LANES);
+        let min = Vec2::new(self.min_x[lane][idx], self.min_y[lane][idx]);
+        let max = Vec2::new(self.max_x[lane][idx], self.max_y[lane][idx]);
+        (0.5*(min + max), 0.5*(max - min))
+    }
+
+}
+
+impl FromIterator<(Vec2, Vec2)> for RectBatch {
+    fn from_iter<T: IntoIterator<Item = (Vec2, Vec2)>>(iter: T) -> Self {
+        let mut result = Self::new();
+        iter.into_iter().for_each(|(origin, size)| result.push(origin, size));
+        result
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CircleBatch {
+    origin_x:  Vec<Vec4>,
+    origin_y:  Vec<Vec4>,
+    radius_sq: Vec<Vec4>,
+    len:       usize,
+}
+
+impl CircleBatch {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn push(&mut self, origin: Vec2, radius: f32) {
+        let (lane, idx) = (self.len / LANES, self.len % LANES);
+        if idx == 0 {
+            for v in [&mut self.origin_x, &mut self.origin_y, &mut self.radius_sq] {
+                v.push(Vec4::ZERO);
+            }
+        }
+
+        self.origin_x[lane][idx]  = origin.x;
+        self.origin_y[lane][idx]  = origin.y;
+        self.radius_sq[lane][idx] = radius*radius;
+        self.len += 1;
+    }
+
+    pub fn get(&self, index: usize) -> (Vec2, f32) {
+        debug_assert!(index < self.len);
+        let (lane, idx) = (index / LANES, index % LANES);
+        (Vec2::new(self.origin_x[lane][idx], self.origin_y[lane][idx]), self.radius_sq[lane][idx].sqrt())
+    }
+
+}
+
+impl FromIterator<(Vec2, f32)> for CircleBatch {
+    fn from_iter<T: IntoIterator<Item = (Vec2, f32)>>(iter: T) -> Self {
+        let mut result = Self::new();
+        iter.into_iter().for_each(|(origin, radius)| result.push(origin, radius));
+        result
+    }
+}
+
+// NOTE These match RayCaster::test, so the ray's range applies and a shape
+//      we start inside is hit with a negative entry distance. Only the entry
+//      distance is found for every shape, the full intersection is only worth
+//      working out for the ones we keep.
+
+impl RayCaster {
+
+    // NOTE Misses are written as infinity
+    pub fn test_rect_batch(&self, rects: &RectBatch, out: &mut [f32]) {
+        debug_assert!(out.len() >= rects.len());
+        for lane in 0..rects.min_x.len() {
+            let (hit, enter) = self.test_rect_lane(rects, lane);
+            write_lane(out, lane, Vec4::select(hit, enter, Vec4::INFINITY));
+        }
+    }
+
+    pub fn test_rect_batch_closest(&self, rects: &RectBatch) -> Option<(usize, RayIntersection)> {
+        let index = find_closest_lane(rects.min_x.len(), |lane| self.test_rect_lane(rects, lane))?;
+        let (origin, size) = rects.get(index);
+        self.test_rect(origin, size).map(|[enter, _]| (index, enter))
+    }
+
+    pub fn test_circle_batch(&self, circles: &CircleBatch, out: &mut [f32]) {
+        debug_assert!(out.len() >= circles.len());
+        for lane in 0..circles.origin_x.len() {
+            let (hit, enter) = self.test_circle_lane(circles, lane);
+            write_lane(out, lane, Vec4::select(hit, enter, Vec4::INFINITY));
+        }
+    }
+
+    pub fn test_circle_batch_closest(&self, circles: &CircleBatch) -> Option<(usize, RayIntersection)> {
+        let index = find_closest_lane(circles.origin_x.len(), |lane| self.test_circle_lane(circles, lane))?;
+        let (origin, radius) = circles.get(index);
+        self.test_circle(origin, radius).map(|[enter, _]| (index, enter))
+    }
+
+    fn test_rect_lane(&self, rects: &RectBatch, lane: usize) -> (BVec4A, Vec4) {
+        // NOTE Axis aligned directions give an infinite inverse, same as the scalar test
+        let (origin, direction_inv) = (self.origin(), self.direction().recip());
+        let t = [-Vec4::INFINITY, Vec4::INFINITY];
+        let t = Self::test_rect_minmax_lane(origin.x, direction_inv.x, rects.min_x[lane], rects.max_x[lane], t);
+        let [enter, exit] = Self::test_rect_minmax_lane(origin.y, direction_inv.y, rects.min_y[lane], rects.max_y[lane], t);
+        let hit = enter.cmplt(exit) & self.test_range_lane(enter, exit) & get_lane_mask(rects.len, lane);
+        (hit, enter)
+    }
+
+    // NOTE Same as test_rect_minmax, the NaN from an axis aligned ray starting on
+    //      an edge (0*inf) must be the first argument so it's ignored by min/max.
+    fn test_rect_minmax_lane(origin: f32, direction_inv: f32, min: Vec4, max: Vec4, t: [Vec4; 2]) -> [Vec4; 2] {
+        let t1 = (min - Vec4::splat(origin)) * direction_inv;
+        let t2 = (max - Vec4::splat(origin)) * direction_inv;
+        [
+            t1.max(t[0]).min(t2.max(t[0])),
+            t1.min(t[1]).max(t2.min(t[1])),
+        ]
+    }
+
+    fn test_circle_lane(&self, circles: &CircleBatch, lane: usize) -> (BVec4A, Vec4) {
+        // Same as offset_origin_dp, but for 4 circles at once. Done the same way
+        // so rays that only just touch agree with the scalar test.
+        let (origin, direction) = (self.origin(), self.direction());
+        let (origin_x, origin_y) = (circles.origin_x[lane], circles.origin_y[lane]);
+        let dp_0 = Vec4::splat(direction.dot(origin))      - (direction.x*origin_x + direction.y*origin_y);
+        let dp_1 = Vec4::splat(direction.perp_dot(origin)) - (direction.x*origin_y - direction.y*origin_x);
+
+        // Most rays miss most circles, so skip the square roots when we can
+        let offset_sq = circles.radius_sq[lane] - dp_1*dp_1;
+        let hit = offset_sq.cmpge(Vec4::ZERO) & get_lane_mask(circles.len, lane);
+        if !hit.any() {
+            return (hit, Vec4::INFINITY);
+        }
+
+        let offset = Vec4::from_array(offset_sq.max(Vec4::ZERO).to_array().map(f32::sqrt));
+        let [enter, exit] = [-offset - dp_0, offset - dp_0];
+        (hit & self.test_range_lane(enter, exit), enter)
+    }
+
+    fn test_range_lane(&self, enter: Vec4, exit: Vec4) -> BVec4A {
+        enter.cmple(Vec4::splat(self.max_distance())) & exit.cmpge(Vec4::splat(self.min_distance()))
+    }
+
+}
+
+fn get_lane_mask(len: usize, lane: usize) -> BVec4A {
+    let base = lane*LANES;
+    if base + LANES <= len {
+        BVec4A::splat(true)
+    } else {
+        BVec4A::new(base < len, base + 1 < len, base + 2 < len, base + 3 < len)
+    }
+}
+
+fn write_lane(out: &mut [f32], lane: usize, values: Vec4) {
+    let base  = lane*LANES;
+    let count = out.len().saturating_sub(base).min(LANES);
+    out[base..base+count].copy_from_slice(&values.to_array()[..count]);
+}
+
+fn find_closest_lane(lanes: usize, test: impl Fn(usize) -> (BVec4A, Vec4)) -> Option<usize> {
+    let mut closest = None;
+    let mut closest_dist = f32::INFINITY;
+    for lane in 0..lanes {
+        let (hit, enter) = test(lane);
+        let enter = Vec4::select(hit, enter, Vec4::INFINITY);
+        if enter.min_element() >= closest_dist {
+            continue;
+        }
+
+        for (idx, &dist) in enter.to_array().iter().enumerate() {
+            if dist < closest_dist {
+                closest      = Some(lane*LANES + idx);
+                closest_dist = dist;
+            }
+        }
+    }
+    closest
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::Vec2;
+    use crate::prelude::{Ball, BoxAligned, CircleBatch, RayCaster, RectBatch};
+    use crate::test_util::Rng;
+
+    #[test]
+    fn check_batch_matches_scalar() {
+        let mut rng  = Rng(0x1234_5678);
+        let mut next = move || rng.range(0.0, 1.0);
+
+        // Mostly on the grid, so we get plenty of rays along edges and corners
+        let mut point = || Vec2::new((next()*16.0).round()*0.25 - 2.0, (next()*16.0).round()*0.25 - 2.0);
+        let rects:   Vec<_> = (0..37).map(|_| (point(), point().abs() + 0.25)).collect();
+        let circles: Vec<_> = (0..37).map(|_| (point(), point().x.abs())).collect();
+        let rect_batch:   RectBatch   = rects.iter().copied().collect();
+        let circle_batch: CircleBatch = circles.iter().copied().collect();
+
+        let directions = [Vec2::X, -Vec2::Y, Vec2::new(1.0, 1.0), Vec2::new(-2.0, 1.0), Vec2::ZERO];
+        let mut out = [0.0; 37];
+        for i in 0..200 {
+            let ray = RayCaster::new(point(), directions[i % directions.len()]);
+            let ray = if i % 3 == 0 { ray.with_range(0.0, 1.5) } else { ray };
+
+            ray.test_rect_batch(&rect_batch, &mut out);
+            for (&(origin, size), &dist) in rects.iter().zip(out.iter()) {
+                let expected = ray.test(&BoxAligned::new(origin, size)).map_or(f32::INFINITY, |[v, _]| v.distance);
+                assert!(dist == expected || (dist - expected).abs() < 1.0e-4, "rect {dist} vs {expected}");
+            }
+
+            ray.test_circle_batch(&circle_batch, &mut out);
+            for (&(origin, radius), &dist) in circles.iter().zip(out.iter()) {
+                let expected = ray.test(&Ball::new(origin, radius)).map_or(f32::INFINITY, |[v, _]| v.distance);
+                assert!(dist == expected || (dist - expected).abs() < 1.0e-4, "circle {dist} vs {expected}");
+            }
+
+            let closest = rects.iter()
+                .map(|&(origin, size)| ray.test(&BoxAligned::new(origin, size)).map_or(f32::INFINITY, |[v, _]| v.distance))
+                .fold(f32::INFINITY, f32::min);
+            let found = ray.test_rect_batch_closest(&rect_batch).map_or(f32::INFINITY, |(_, v)| v.distance);
+            assert!(found == closest || (found - closest).abs() < 1.0e-4);
+        }
+    }
+}
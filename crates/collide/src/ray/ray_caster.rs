@@ -2,6 +2,8 @@
 
 use bevy::prelude::Vec2;
 
+use crate::prelude::{Ball, ShapeCombined, ShapeMoving, ShapeStatic};
+
 use super::{RayTarget, RayIntersection};
 
 pub struct RayCaster {
@@ -9,19 +11,67 @@ pub struct RayCaster {
     origin_dp:     [f32; 2],
     direction:     Vec2,
     direction_inv: Vec2,
+    range:         [f32; 2],
+    degenerate:    bool,
 }
 
+// NOTE Directions are always normalised. A zero length (or non-finite) direction
+//      can't be normalised, so it's treated as a zero length segment along X
+//      instead, which only hits shapes that contain the origin.
+
 impl RayCaster {
 
     pub fn new(origin: Vec2, direction: Vec2) -> Self {
+        debug_assert!(origin.is_finite(), "Ray origin must be finite");
+        match direction.try_normalize() {
+            Some(direction) => Self::new_unchecked(origin, direction, [-f32::INFINITY, f32::INFINITY], false),
+            None            => Self::new_unchecked(origin, Vec2::X,   [0.0, 0.0], true),
+        }
+    }
+
+    pub fn try_new(origin: Vec2, direction: Vec2) -> Result<Self, &'static str> {
+        if !origin.is_finite() {
+            return Err("Ray origin is not finite");
+        }
+
+        if !direction.is_finite() {
+            return Err("Ray direction is not finite");
+        }
+
+        match direction.try_normalize() {
+            Some(direction) => Ok(Self::new_unchecked(origin, direction, [-f32::INFINITY, f32::INFINITY], false)),
+            None            => Err("Ray direction has no length"),
+        }
+    }
+
+    pub fn new_segment(origin: Vec2, direction: Vec2, min_dist: f32, max_dist: f32) -> Self {
+        Self::new(origin, direction).with_range(min_dist, max_dist)
+    }
+
+    pub fn with_range(self, min_dist: f32, max_dist: f32) -> Self {
+        if self.degenerate {
+            self
+        } else {
+            Self{ range: [min_dist, max_dist], ..self }
+        }
+    }
+
+    fn new_unchecked(origin: Vec2, direction: Vec2, range: [f32; 2], degenerate: bool) -> Self {
         Self{
             origin, 
             origin_dp: [direction.dot(origin), direction.perp_dot(origin)],
             direction,
+            // NOTE Axis aligned directions give an infinite inverse, which the rect test relies on
             direction_inv: Vec2::new(1.0/direction.x, 1.0/direction.y),
+            range,
+            degenerate,
         }
     }
 
+    pub fn is_degenerate(&self) -> bool {
+        self.degenerate
+    }
+
     pub fn origin(&self) -> Vec2 {
         self.origin
     }
@@ -30,20 +80,64 @@ impl RayCaster {
         self.direction
     }
 
+    pub fn min_distance(&self) -> f32 {
+        self.range[0]
+    }
+
+    pub fn max_distance(&self) -> f32 {
+        self.range[1]
+    }
+
+    pub fn is_in_range(&self, distance: f32) -> bool {
+        self.range[0] <= distance && distance <= self.range[1]
+    }
+
 }
 
+// NOTE The range only applies here, the shape specific tests below are unbounded.
+//      A pair counts as a hit when it overlaps the range at all, so a segment
+//      that starts inside a shape still hits it, with the entry behind the minimum.
+
 impl RayCaster {
 
     pub fn test(&self, other: &impl RayTarget) -> Option<[RayIntersection; 2]> {
-        other.raycast(self)
+        other.raycast(self).filter(|[enter, exit]| enter.distance <= self.range[1] && exit.distance >= self.range[0])
     }
 
     pub fn test_enter(&self, other: &impl RayTarget) -> Option<RayIntersection> {
-        other.raycast_enter(self)
+        other.raycast_enter(self).filter(|v| self.is_in_range(v.distance))
     }
 
     pub fn test_exit(&self, other: &impl RayTarget) -> Option<RayIntersection> {
-        other.raycast_exit(self)
+        other.raycast_exit(self).filter(|v| self.is_in_range(v.distance))
+    }
+
+    // NOTE Thick rays sweep a ball along the ray, the points are where its centre stops
+    pub fn test_thick_static(&self, other: &ShapeStatic, radius: f32) -> Option<[RayIntersection; 2]> {
+        self.test(&ShapeCombined::between_moving_and_static(&self.create_thick_ball(radius), other))
+    }
+
+    pub fn test_thick_moving(&self, other: &ShapeMoving, radius: f32) -> Option<[RayIntersection; 2]> {
+        self.test(&ShapeCombined::between_moving(&self.create_thick_ball(radius), other))
+    }
+
+    fn create_thick_ball(&self, radius: f32) -> ShapeMoving {
+        Ball::new(self.origin, radius).into()
+    }
+
+    pub fn test_one_way(&self, other: &impl RayTarget, normal: Vec2) -> Option<[RayIntersection; 2]> {
+        self.test(other).filter(|[enter, _]| self.is_one_way_hit(enter, normal))
+    }
+
+    pub fn test_enter_one_way(&self, other: &impl RayTarget, normal: Vec2) -> Option<RayIntersection> {
+        self.test_enter(other).filter(|enter| self.is_one_way_hit(enter, normal))
+    }
+
+    // NOTE One-way shapes only block rays travelling against their normal, and
+    //      only if the ray started outside them. Otherwise something that's
+    //      halfway through (ie. jumping up through a ledge) would get stuck.
+    pub fn is_one_way_hit(&self, enter: &RayIntersection, normal: Vec2) -> bool {
+        self.direction.dot(normal) < 0.0 && enter.distance >= 0.0
     }
 
 }
@@ -59,7 +153,10 @@ impl RayCaster {
         RayCaster::calc_circle_center_offset(ray_dp, radius).map(|offset| {
             let distances = [-offset - ray_dp[0], offset - ray_dp[0]];
             let points  = distances.map(|d| self.origin + self.direction*d);
-            let normals = points.map(|p| p.normalize());
+            let normals = [
+                Self::calc_circle_normal(points[0], origin, -self.direction),
+                Self::calc_circle_normal(points[1], origin,  self.direction),
+            ];
     
             [
                 RayIntersection{distance: distances[0], point: points[0], normal: normals[0]},
@@ -74,7 +171,7 @@ impl RayCaster {
 
             let distance = -offset - ray_dp[0];
             let point  = self.origin + self.direction*distance;
-            let normal = point.normalize();
+            let normal = Self::calc_circle_normal(point, origin, -self.direction);
     
             RayIntersection{distance, point, normal}
         })
@@ -86,7 +183,7 @@ impl RayCaster {
 
             let distance = offset - ray_dp[0];
             let point  = self.origin + self.direction*distance;
-            let normal = point.normalize();
+            let normal = Self::calc_circle_normal(point, origin, self.direction);
     
             RayIntersection{distance, point, normal}
         })
@@ -99,11 +196,18 @@ impl RayCaster {
         ]
     }
 
+    // NOTE A zero radius circle has no surface to take the normal from, so we fallback
+    //      to facing the ray, the same as if we'd hit it dead center.
+    fn calc_circle_normal(point: Vec2, origin: Vec2, fallback: Vec2) -> Vec2 {
+        (point - origin).try_normalize().unwrap_or(fallback)
+    }
+
     fn calc_circle_center_offset(ray_dp: [f32; 2], radius: f32) -> Option<f32> {
         if radius < ray_dp[1].abs() { 
             None 
         } else {
-            Some(radius*(1.0-(ray_dp[1]/radius).powi(2)).sqrt())
+            // NOTE Not divided through by the radius, so zero radius circles still work
+            Some((radius*radius - ray_dp[1]*ray_dp[1]).max(0.0).sqrt())
         }
     }
 
@@ -280,12 +384,31 @@ impl RayCaster {
         RayIntersection::find_polygon_entry_exit((0..points.len()).filter_map(|i| self.test_line_opt(points[i], normals[i].perp(), lengths[i])))
     }
 
+    // NOTE Concave polygons can be entered and exited more than once, so the crossings
+    //      are paired up in order and we keep the first pair that isn't behind us.
+    //      Edges only count when their ends are on opposite sides of the ray, so a
+    //      vertex on the ray is crossed once (or not at all if we just graze it).
+    pub fn test_polygon_concave_at_origin(&self, points: &[Vec2], normals: &[Vec2]) -> Option<[RayIntersection; 2]> {
+        let side = |v: Vec2| self.direction.perp_dot(v - self.origin) > 0.0;
+        let mut crossings: Vec<RayIntersection> = (0..points.len())
+            .filter(|&i| side(points[i]) != side(points[(i+1)%points.len()]))
+            .filter_map(|i| self.test_line_infinite_opt(points[i], normals[i].perp()))
+            .collect();
+        crossings.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+
+        let mut pairs = crossings.chunks_exact(2).map(|v| [v[0], v[1]]);
+        pairs.clone().find(|[_, exit]| exit.distance >= 0.0).or_else(|| pairs.next_back())
+    }
+
 }
 
 // /////////////////// //
 // // Raytest Lines // //
 // /////////////////// //
 
+// NOTE Line normals face to the right of the line, which is outwards for
+//      counter-clockwise polygons. The ray can hit them from either side.
+
 impl RayCaster {
 
     pub fn test_line(&self, from: Vec2, to: Vec2) -> Option<RayIntersection> {
@@ -300,7 +423,7 @@ impl RayCaster {
             Some(RayIntersection {
                 distance, 
                 point:  self.origin + self.direction*distance, 
-                normal: self.direction.perp() 
+                normal: -dir.perp() 
             }) 
         } else { 
             None 
@@ -316,13 +439,14 @@ impl RayCaster {
         self.calc_ray_intersection_dp(from, dir).map(|[distance, _]| RayIntersection {
             distance, 
             point: self.origin + self.direction*distance, 
-            normal: self.direction.perp() 
+            normal: -dir.perp() 
         })
     }
 
     fn calc_ray_intersection_dp(&self, other_origin: Vec2, other_dir: Vec2) -> Option<[f32; 2]> {
+        // Parallel lines never intersect
         let inv_pdp = 1.0/self.direction.perp_dot(other_dir);
-        if inv_pdp != f32::INFINITY {  
+        if inv_pdp.is_finite() {  
             Some([
                       other_dir.perp_dot( self.origin - other_origin) * inv_pdp,
                 -self.direction.perp_dot(other_origin -  self.origin) * inv_pdp,
@@ -332,4 +456,211 @@ impl RayCaster {
         }
     }
 
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::Vec2;
+    use crate::prelude::*;
+    use crate::test_util::Rng;
+
+    #[test]
+    fn check_segment() {
+        let target = BoxAligned::new(Vec2::new(2.0, 0.0), Vec2::splat(0.5));
+
+        let ray = RayCaster::new_segment(Vec2::ZERO, Vec2::X, 0.0, 1.0);
+        assert!(ray.test(&target).is_none());
+
+        let ray = RayCaster::new_segment(Vec2::ZERO, Vec2::X, 0.0, 1.5);
+        assert_eq!(ray.test_enter(&target).unwrap().distance, 1.5);
+
+        // Behind the origin
+        let ray = RayCaster::new_segment(Vec2::new(3.0, 0.0), Vec2::X, 0.0, 10.0);
+        assert!(ray.test(&target).is_none());
+
+        // Starting inside still hits, but the entry isn't in range
+        let ray = RayCaster::new_segment(Vec2::new(2.0, 0.0), Vec2::X, 0.0, 10.0);
+        assert!(ray.test(&target).is_some());
+        assert!(ray.test_enter(&target).is_none());
+    }
+
+    #[test]
+    fn check_thick() {
+        let target: ShapeStatic = Ball::new(Vec2::new(2.0, 0.6), 0.5).into();
+
+        let ray = RayCaster::new(Vec2::ZERO, Vec2::X);
+        assert!(ray.test(&target).is_none());
+
+        let [enter, _] = ray.test_thick_static(&target, 0.5).unwrap();
+        assert!((enter.distance - 1.2).abs() < 1.0e-5);
+        assert!((enter.normal - Vec2::new(-0.8, -0.6)).length() < 1.0e-5);
+    }
+
+    #[test]
+    fn check_one_way() {
+        let ledge = BoxAligned::new(Vec2::ZERO, Vec2::new(1.0, 0.25));
+
+        let falling = RayCaster::new(Vec2::new(0.0, 1.0), -Vec2::Y);
+        assert!(falling.test_one_way(&ledge, Vec2::Y).is_some());
+
+        let rising = RayCaster::new(Vec2::new(0.0, -1.0), Vec2::Y);
+        assert!(rising.test(&ledge).is_some());
+        assert!(rising.test_one_way(&ledge, Vec2::Y).is_none());
+
+        let inside = RayCaster::new(Vec2::ZERO, -Vec2::Y);
+        assert!(inside.test_one_way(&ledge, Vec2::Y).is_none());
+    }
+
+    #[test]
+    fn check_degenerate_directions() {
+        let target = BoxAligned::new(Vec2::ZERO, Vec2::splat(0.5));
+
+        let ray = RayCaster::new(Vec2::new(-2.0, 0.0), Vec2::new(3.0, 0.0));
+        assert_eq!(ray.direction(), Vec2::X);
+        assert_eq!(ray.test_enter(&target).unwrap().distance, 1.5);
+
+        // Zero length rays only hit what they start inside
+        let ray = RayCaster::new(Vec2::new(-2.0, 0.0), Vec2::ZERO);
+        assert!(ray.is_degenerate());
+        assert!(ray.test(&target).is_none());
+        let ray = RayCaster::new_segment(Vec2::new(0.25, 0.0), Vec2::ZERO, 0.0, 10.0);
+        assert!(ray.test(&target).is_some());
+
+        assert!(RayCaster::try_new(Vec2::ZERO, Vec2::ZERO).is_err());
+        assert!(RayCaster::try_new(Vec2::ZERO, Vec2::new(f32::NAN, 1.0)).is_err());
+        assert!(RayCaster::try_new(Vec2::new(f32::INFINITY, 0.0), Vec2::X).is_err());
+        assert!(RayCaster::try_new(Vec2::ZERO, Vec2::new(0.0, -2.0)).is_ok());
+    }
+
+    fn get_point(rng: &mut Rng) -> Vec2 {
+        let point = rng.point(3.0);
+        match rng.next() % 3 {
+            // Snap to half units so we land exactly on edges and corners
+            0 => (point*2.0).round()*0.5,
+            _ => point,
+        }
+    }
+
+    fn get_direction(rng: &mut Rng) -> Vec2 {
+        match rng.next() % 8 {
+            0 => Vec2::X,
+            1 => -Vec2::Y,
+            2 => Vec2::ZERO,
+            3 => Vec2::new(1.0e-30, 0.0),
+            4 => Vec2::new(1.0e30, -1.0e30),
+            5 => Vec2::new(-0.0, 1.0),
+            _ => rng.point(2.0),
+        }
+    }
+
+    fn is_valid(v: &RayIntersection) -> bool {
+        v.distance.is_finite() && v.point.is_finite() && v.normal.is_finite()
+    }
+
+    #[test]
+    fn check_no_nans() {
+        let statics: [ShapeStatic; 10] = [
+            Ball::new(Vec2::ZERO, 1.0).into(),
+            BoxAligned::new(Vec2::new(0.5, 0.0), Vec2::splat(0.5)).into(),
+            BoxAlignedRound::new(Vec2::ZERO, Vec2::splat(0.5), 0.5).into(),
+            BoxOriented::new(Vec2::ZERO, Vec2::new(1.0, 0.5), Vec2::ONE.normalize()).into(),
+            BoxOrientedRound::new(Vec2::ZERO, Vec2::new(1.0, 0.5), Vec2::Y, 0.25).into(),
+            Ramp::new_from_size_centered(Vec2::ZERO, Vec2::ONE, Vec2::ONE).into(),
+            RampRound::new(Vec2::ZERO, Vec2::new(1.0, -1.0).normalize(), 1.0, 0.5).into(),
+            Capsule::new_vertical(Vec2::ZERO, 2.0, 0.5).into(),
+            PolygonSmall::new_from_points([Vec2::new(-1.0, -1.0), Vec2::new(1.0, -1.0), Vec2::new(0.0, 1.0)]).into(),
+            PolygonSmallRound::new_from_points([Vec2::new(-1.0, -1.0), Vec2::new(1.0, -1.0), Vec2::new(0.0, 1.0)], 0.5).into(),
+        ];
+
+        let movers: [ShapeMoving; 3] = [
+            Ball::new(Vec2::ZERO, 0.5).into(),
+            BoxAligned::new(Vec2::ZERO, Vec2::splat(0.5)).into(),
+            Capsule::new_vertical(Vec2::ZERO, 1.0, 0.25).into(),
+        ];
+
+        let mut rng = Rng(0x2545_f491);
+        for _ in 0..2000 {
+            let origin = get_point(&mut rng);
+            let ray = RayCaster::new_segment(origin, get_direction(&mut rng), 0.0, rng.range(0.0, 4.0));
+            assert!(ray.direction().is_finite());
+
+            for target in statics.iter() {
+                if let Some(hits) = ray.test(target) {
+                    assert!(hits.iter().all(is_valid), "{ray_origin:?} {target:?} {hits:?}", ray_origin = ray.origin());
+                }
+                
+                if let Some([enter, exit]) = ray.test_thick_static(target, 0.25) {
+                    assert!(is_valid(&enter) && is_valid(&exit), "{target:?} {enter:?} {exit:?}");
+                }
+
+                for mover in movers.iter() {
+                    let mut mover = *mover;
+                    mover.set_origin(origin);
+                    let combined = ShapeCombined::between_moving_and_static(&mover, target);
+                    if let Some([enter, exit]) = ray.test(&combined) {
+                        assert!(is_valid(&enter) && is_valid(&exit), "{mover:?} {target:?} {enter:?} {exit:?}");
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_normals_for(ray: &RayCaster, target: &(impl RayTarget + ShapeClosest), label: &str) {
+        let Some([enter, exit]) = ray.test(target) else {
+            return;
+        };
+
+        for (hit, facing) in [(enter, -1.0), (exit, 1.0)] {
+            assert!((hit.normal.length() - 1.0).abs() < 1.0e-4, "{label} {hit:?}");
+            assert!(hit.normal.dot(ray.direction())*facing >= -1.0e-4, "{label} {hit:?}");
+
+            // The surface normal must agree with the closest point, unless we're on a corner
+            let closest = target.closest_point(hit.point);
+            assert!(closest.distance.abs() < 1.0e-3, "{label} {hit:?} {closest:?}");
+            let is_corner = (closest.point - hit.point).length() > 1.0e-3;
+            assert!(is_corner || closest.normal.dot(hit.normal) > 0.999, "{label} {hit:?} {closest:?}");
+        }
+    }
+
+    #[test]
+    fn check_normals() {
+        let offset = Vec2::new(3.0, -2.0);
+        let statics: [ShapeStatic; 10] = [
+            Ball::new(offset, 1.0).into(),
+            BoxAligned::new(offset, Vec2::new(1.0, 0.5)).into(),
+            BoxAlignedRound::new(offset, Vec2::splat(0.5), 0.5).into(),
+            BoxOriented::new(offset, Vec2::new(1.0, 0.5), Vec2::ONE.normalize()).into(),
+            BoxOrientedRound::new(offset, Vec2::new(1.0, 0.5), Vec2::Y, 0.25).into(),
+            Ramp::new_from_size_centered(offset, Vec2::ONE, Vec2::ONE).into(),
+            RampRound::new(offset, Vec2::new(1.0, -1.0).normalize(), 1.0, 0.5).into(),
+            Capsule::new(offset, Vec2::new(2.0, 1.0).normalize(), 2.0, 0.5).into(),
+            PolygonSmall::new_from_points([-Vec2::ONE, Vec2::new(1.0, -1.0), Vec2::Y].map(|v| offset + v)).into(),
+            PolygonSmallRound::new_from_points([-Vec2::ONE, Vec2::new(1.0, -1.0), Vec2::Y].map(|v| offset + v), 0.5).into(),
+        ];
+
+        let movers: [ShapeMoving; 5] = [
+            Ball::new(Vec2::ZERO, 0.5).into(),
+            BoxAligned::new(Vec2::ZERO, Vec2::new(0.5, 0.25)).into(),
+            BoxOriented::new(Vec2::ZERO, Vec2::new(0.5, 0.25), Vec2::new(3.0, 1.0).normalize()).into(),
+            Capsule::new_vertical(Vec2::ZERO, 1.0, 0.25).into(),
+            PolygonSmall::new_from_points([Vec2::new(-0.5, -0.5), Vec2::new(0.5, -0.5), Vec2::new(0.0, 0.5)]).into(),
+        ];
+
+        let mut rng = Rng(0x9e37_79b9);
+        for _ in 0..500 {
+            let origin    = offset + Vec2::new(rng.range(-5.0, 5.0), rng.range(-5.0, 5.0));
+            let target    = offset + Vec2::new(rng.range(-1.0, 1.0), rng.range(-1.0, 1.0));
+            let ray = RayCaster::new(origin, target - origin);
+
+            for shape in statics.iter() {
+                check_normals_for(&ray, shape, &format!("{shape:?}"));
+
+                for mover in movers.iter() {
+                    let mut mover = *mover;
+                    mover.set_origin(origin);
+                    check_normals_for(&ray, &ShapeCombined::between_moving_and_static(&mover, shape), &format!("{mover:?} {shape:?}"));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,10 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+mod ray_caster;
+pub use ray_caster::*;
+
+mod ray_intersection;
+pub use ray_intersection::*;
+
+mod ray_target;
+pub use ray_target::*;
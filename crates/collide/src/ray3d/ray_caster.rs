@@ -0,0 +1,265 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use bevy::prelude::Vec3;
+
+use crate::prelude3d::{Ball, ShapeCombined, ShapeMoving, ShapeStatic, cast_hull, find_support};
+
+use super::{RayTarget, RayIntersection};
+
+pub struct RayCaster {
+    origin:     Vec3,
+    direction:  Vec3,
+    range:      [f32; 2],
+    degenerate: bool,
+}
+
+// NOTE Same as the 2D caster, a direction that can't be normalised is treated
+//      as a zero length segment along X, which only hits shapes containing the origin.
+
+impl RayCaster {
+
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        debug_assert!(origin.is_finite(), "Ray origin must be finite");
+        match direction.try_normalize() {
+            Some(direction) => Self::new_unchecked(origin, direction, [-f32::INFINITY, f32::INFINITY], false),
+            None            => Self::new_unchecked(origin, Vec3::X,   [0.0, 0.0], true),
+        }
+    }
+
+    pub fn try_new(origin: Vec3, direction: Vec3) -> Result<Self, &'static str> {
+        if !origin.is_finite() {
+            return Err("Ray origin is not finite");
+        }
+
+        if !direction.is_finite() {
+            return Err("Ray direction is not finite");
+        }
+
+        match direction.try_normalize() {
+            Some(direction) => Ok(Self::new_unchecked(origin, direction, [-f32::INFINITY, f32::INFINITY], false)),
+            None            => Err("Ray direction has no length"),
+        }
+    }
+
+    pub fn new_segment(origin: Vec3, direction: Vec3, min_dist: f32, max_dist: f32) -> Self {
+        Self::new(origin, direction).with_range(min_dist, max_dist)
+    }
+
+    pub fn with_range(self, min_dist: f32, max_dist: f32) -> Self {
+        if self.degenerate {
+            self
+        } else {
+            Self{ range: [min_dist, max_dist], ..self }
+        }
+    }
+
+    fn new_unchecked(origin: Vec3, direction: Vec3, range: [f32; 2], degenerate: bool) -> Self {
+        Self{origin, direction, range, degenerate}
+    }
+
+    pub fn is_degenerate(&self) -> bool {
+        self.degenerate
+    }
+
+    pub fn origin(&self) -> Vec3 {
+        self.origin
+    }
+
+    pub fn direction(&self) -> Vec3 {
+        self.direction
+    }
+
+    pub fn min_distance(&self) -> f32 {
+        self.range[0]
+    }
+
+    pub fn max_distance(&self) -> f32 {
+        self.range[1]
+    }
+
+    pub fn is_in_range(&self, distance: f32) -> bool {
+        self.range[0] <= distance && distance <= self.range[1]
+    }
+
+}
+
+// NOTE The range only applies here, the shape specific tests below are unbounded.
+
+impl RayCaster {
+
+    pub fn test(&self, other: &impl RayTarget) -> Option<[RayIntersection; 2]> {
+        other.raycast(self).filter(|[enter, exit]| enter.distance <= self.range[1] && exit.distance >= self.range[0])
+    }
+
+    pub fn test_enter(&self, other: &impl RayTarget) -> Option<RayIntersection> {
+        other.raycast_enter(self).filter(|v| self.is_in_range(v.distance))
+    }
+
+    pub fn test_exit(&self, other: &impl RayTarget) -> Option<RayIntersection> {
+        other.raycast_exit(self).filter(|v| self.is_in_range(v.distance))
+    }
+
+    // NOTE Thick rays sweep a ball along the ray, the points are where its centre stops
+    pub fn test_thick_static(&self, other: &ShapeStatic, radius: f32) -> Option<[RayIntersection; 2]> {
+        self.test(&ShapeCombined::between_moving_and_static(&self.create_thick_ball(radius), other))
+    }
+
+    pub fn test_thick_moving(&self, other: &ShapeMoving, radius: f32) -> Option<[RayIntersection; 2]> {
+        self.test(&ShapeCombined::between_moving(&self.create_thick_ball(radius), other))
+    }
+
+    fn create_thick_ball(&self, radius: f32) -> ShapeMoving {
+        Ball::new(self.origin, radius).into()
+    }
+
+    pub fn test_one_way(&self, other: &impl RayTarget, normal: Vec3) -> Option<[RayIntersection; 2]> {
+        self.test(other).filter(|[enter, _]| self.is_one_way_hit(enter, normal))
+    }
+
+    pub fn test_enter_one_way(&self, other: &impl RayTarget, normal: Vec3) -> Option<RayIntersection> {
+        self.test_enter(other).filter(|enter| self.is_one_way_hit(enter, normal))
+    }
+
+    pub fn is_one_way_hit(&self, enter: &RayIntersection, normal: Vec3) -> bool {
+        self.direction.dot(normal) < 0.0 && enter.distance >= 0.0
+    }
+
+}
+
+// //////////////////// //
+// // Raytest Sphere // //
+// //////////////////// //
+
+impl RayCaster {
+
+    pub fn test_sphere(&self, origin: Vec3, radius: f32) -> Option<[RayIntersection; 2]> {
+        let offset  = self.origin - origin;
+        let along   = self.direction.dot(offset);
+        let dist_sq = (offset - self.direction*along).length_squared();
+        if radius*radius < dist_sq {
+            return None;
+        }
+
+        // NOTE Not divided through by the radius, so zero radius spheres still work
+        let half      = (radius*radius - dist_sq).max(0.0).sqrt();
+        let distances = [-half - along, half - along];
+        let points    = distances.map(|d| self.origin + self.direction*d);
+        Some([
+            RayIntersection{distance: distances[0], point: points[0], normal: (points[0] - origin).try_normalize().unwrap_or(-self.direction)},
+            RayIntersection{distance: distances[1], point: points[1], normal: (points[1] - origin).try_normalize().unwrap_or( self.direction)},
+        ])
+    }
+
+}
+
+// //////////////////// //
+// // Raytest Planes // //
+// //////////////////// //
+
+impl RayCaster {
+
+    // NOTE Planes are (normal, offset) facing outwards, so a point is inside
+    //      when normal.dot(point) <= offset for every one of them.
+    pub fn test_planes(&self, planes: &[(Vec3, f32)]) -> Option<[RayIntersection; 2]> {
+        let mut enter = (-f32::INFINITY, -self.direction);
+        let mut exit  = ( f32::INFINITY,  self.direction);
+        for &(normal, offset) in planes {
+            let speed = normal.dot(self.direction);
+            let gap   = offset - normal.dot(self.origin);
+            if speed == 0.0 {
+                // Parallel, so we're either always inside this one or never
+                if gap < 0.0 {
+                    return None;
+                }
+                continue;
+            }
+
+            let distance = gap/speed;
+            if speed < 0.0 {
+                if distance > enter.0 {
+                    enter = (distance, normal);
+                }
+            } else if distance < exit.0 {
+                exit = (distance, normal);
+            }
+        }
+
+        (enter.0 <= exit.0).then(|| [enter, exit].map(|(distance, normal)| RayIntersection{
+            distance,
+            point: self.origin + self.direction*distance,
+            normal,
+        }))
+    }
+
+}
+
+// ////////////////// //
+// // Raytest Hull // //
+// ////////////////// //
+
+impl RayCaster {
+
+    pub fn test_hull_rounded(&self, points: &[Vec3], radius: f32) -> Option<[RayIntersection; 2]> {
+        self.test_support_rounded(|direction| find_support(points, direction), radius)
+    }
+
+    // NOTE There's no closed form for rounded hulls, so we cast in from either
+    //      end of the hull's bounds. Both casts start outside it, which gives
+    //      us the entry and the exit even when the ray starts inside.
+    pub(crate) fn test_support_rounded(&self, support: impl Fn(Vec3) -> Vec3, radius: f32) -> Option<[RayIntersection; 2]> {
+        let min = Vec3::new(support(-Vec3::X).x, support(-Vec3::Y).y, support(-Vec3::Z).z);
+        let max = Vec3::new(support( Vec3::X).x, support( Vec3::Y).y, support( Vec3::Z).z);
+        let extent = 0.5*(max - min).length() + radius;
+        let along  = self.direction.dot(0.5*(min + max) - self.origin);
+        let [start, end] = [along - extent, along + extent];
+
+        let (enter, enter_normal) = cast_hull(&support, radius, self.origin + self.direction*start,  self.direction, end - start)?;
+        let (exit,  exit_normal)  = cast_hull(&support, radius, self.origin + self.direction*end,   -self.direction, end - start)?;
+        let distances = [start + enter, end - exit];
+        Some([
+            RayIntersection{distance: distances[0], point: self.origin + self.direction*distances[0], normal: enter_normal},
+            RayIntersection{distance: distances[1], point: self.origin + self.direction*distances[1], normal: exit_normal},
+        ])
+    }
+
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::{Quat, Vec3};
+    use crate::prelude3d::{BoxAligned, BoxAlignedRound, BoxOriented, Capsule, RayCaster, RayTarget};
+
+    fn is_near(a: Vec3, b: Vec3) -> bool {
+        (a - b).length() < 1.0e-3
+    }
+
+    #[test]
+    fn check_raycast() {
+        let ray = RayCaster::new(Vec3::new(-5.0, 0.5, 0.0), Vec3::X);
+
+        let [enter, exit] = BoxAligned::new(Vec3::ZERO, Vec3::ONE).raycast(&ray).unwrap();
+        assert!((enter.distance - 4.0).abs() < 1.0e-5 && (exit.distance - 6.0).abs() < 1.0e-5);
+        assert!(is_near(enter.normal, -Vec3::X) && is_near(exit.normal, Vec3::X));
+
+        // Turned 45 degrees, the ray now hits an edge at 1/sqrt(2)
+        let turned = BoxOriented::new(Vec3::ZERO, Vec3::splat(0.5), Quat::from_rotation_y(std::f32::consts::FRAC_PI_4));
+        let [enter, _] = turned.raycast(&RayCaster::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::X)).unwrap();
+        assert!((enter.distance - (5.0 - std::f32::consts::FRAC_1_SQRT_2)).abs() < 1.0e-5);
+
+        // Rounded shapes go through the hull cast, so they're a little less exact
+        let [enter, exit] = BoxAlignedRound::new(Vec3::ZERO, Vec3::ONE, 0.5).raycast(&ray).unwrap();
+        assert!((enter.distance - 3.5).abs() < 1.0e-3 && (exit.distance - 6.5).abs() < 1.0e-3);
+        assert!(is_near(enter.normal, -Vec3::X) && is_near(exit.normal, Vec3::X));
+
+        // Starting inside, the entry is behind us
+        let capsule = Capsule::new_vertical(Vec3::new(-5.0, 0.0, 3.0), 3.0, 0.5);
+        let [enter, exit] = capsule.raycast(&RayCaster::new(Vec3::new(-5.0, 0.9, 3.0), Vec3::Y)).unwrap();
+        assert!((enter.distance + 2.4).abs() < 1.0e-3 && (exit.distance - 0.6).abs() < 1.0e-3);
+        assert!(is_near(enter.normal, -Vec3::Y) && is_near(exit.normal, Vec3::Y));
+
+        // Misses, including just past the rounded corner
+        assert!(capsule.raycast(&ray).is_none());
+        assert!(BoxAlignedRound::new(Vec3::ZERO, Vec3::ONE, 0.5).raycast(&RayCaster::new(Vec3::new(-5.0, 1.36, 1.36), Vec3::X)).is_none());
+        assert!(BoxAlignedRound::new(Vec3::ZERO, Vec3::ONE, 0.5).raycast(&RayCaster::new(Vec3::new(-5.0, 1.34, 1.34), Vec3::X)).is_some());
+    }
+}
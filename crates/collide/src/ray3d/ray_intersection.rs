@@ -0,0 +1,10 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use bevy::prelude::Vec3;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RayIntersection {
+    pub distance: f32,
+    pub point:    Vec3,
+    pub normal:   Vec3,
+}
@@ -2,7 +2,7 @@
 
 use bevy::prelude::Vec2;
 
-use crate::prelude::{BoxAligned, RayTarget, RayCaster, RayIntersection, ShapeDebug, ShapeDebugData, ShapeCommon};
+use crate::prelude::{BoxAligned, RayTarget, RayCaster, RayIntersection, ShapeDebug, ShapeDebugData, ShapeCommon, ShapeClosest, ClosestPoint, find_closest_on_circle};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Ball {
@@ -40,4 +40,10 @@ impl ShapeDebug for Ball {
     fn get_debug_shape_data(&self) -> ShapeDebugData {
         ShapeDebugData::circle(self.origin, self.radius)
     }
-}
\ No newline at end of file
+}
+
+impl ShapeClosest for Ball {
+    fn closest_point(&self, point: Vec2) -> ClosestPoint {
+        find_closest_on_circle(self.origin, self.radius, point)
+    }
+}
@@ -2,7 +2,7 @@
 
 use bevy::prelude::Vec2;
 
-use crate::prelude::{RayTarget, RayCaster, RayIntersection, ShapeDebug, ShapeDebugData, ShapeCommon};
+use crate::prelude::{RayTarget, RayCaster, RayIntersection, ShapeDebug, ShapeDebugData, ShapeCommon, ShapeClosest, ClosestPoint, find_closest_on_polygon, get_polygon_data_for_rect};
 
 #[derive(Debug, Clone, Copy)]
 pub struct BoxAligned {
@@ -61,3 +61,10 @@ impl ShapeDebug for BoxAligned {
         )
     }
 }
+
+impl ShapeClosest for BoxAligned {
+    fn closest_point(&self, point: Vec2) -> ClosestPoint {
+        let (points, normals, lengths) = get_polygon_data_for_rect(self.size, Vec2::X);
+        find_closest_on_polygon(self.origin, &points, &normals, &lengths, 0.0, point)
+    }
+}
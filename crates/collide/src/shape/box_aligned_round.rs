@@ -2,7 +2,7 @@
 
 use bevy::prelude::Vec2;
 
-use crate::prelude::{RayTarget, RayCaster, RayIntersection, ShapeDebugData, ShapeDebug, BoxAligned, ShapeCommon};
+use crate::prelude::{RayTarget, RayCaster, RayIntersection, ShapeDebugData, ShapeDebug, BoxAligned, ShapeCommon, ShapeClosest, ClosestPoint, find_closest_on_polygon, get_polygon_data_for_rect};
 
 #[derive(Debug, Clone, Copy)]
 pub struct BoxAlignedRound {
@@ -57,3 +57,9 @@ impl ShapeDebug for BoxAlignedRound {
     }
 }
 
+impl ShapeClosest for BoxAlignedRound {
+    fn closest_point(&self, point: Vec2) -> ClosestPoint {
+        let (points, normals, lengths) = get_polygon_data_for_rect(self.size, Vec2::X);
+        find_closest_on_polygon(self.origin, &points, &normals, &lengths, self.radius, point)
+    }
+}
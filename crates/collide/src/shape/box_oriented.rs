@@ -2,7 +2,7 @@
 
 use bevy::prelude::Vec2;
 
-use crate::prelude::{RayTarget, RayCaster, RayIntersection, ShapeDebug, ShapeDebugData, ShapeCommon, BoxAligned};
+use crate::prelude::{RayTarget, RayCaster, RayIntersection, ShapeDebug, ShapeDebugData, ShapeCommon, BoxAligned, ShapeClosest, ClosestPoint, find_closest_on_polygon, get_polygon_data_for_rect};
 
 #[derive(Debug, Clone, Copy)]
 pub struct BoxOriented {
@@ -84,3 +84,10 @@ impl ShapeDebug for BoxOriented {
         )
     }
 }
+
+impl ShapeClosest for BoxOriented {
+    fn closest_point(&self, point: Vec2) -> ClosestPoint {
+        let (points, normals, lengths) = get_polygon_data_for_rect(self.size, self.direction);
+        find_closest_on_polygon(self.origin, &points, &normals, &lengths, 0.0, point)
+    }
+}
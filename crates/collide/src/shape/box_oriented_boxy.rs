@@ -2,7 +2,7 @@
 
 use bevy::prelude::Vec2;
 
-use crate::prelude::{RayTarget, RayCaster, RayIntersection, ShapeDebug, ShapeDebugData, get_polygon_data_for_oriented_rect_rected, PolygonSmall, ShapeCommon, BoxAligned};
+use crate::prelude::{RayTarget, RayCaster, RayIntersection, ShapeDebug, ShapeDebugData, get_polygon_data_for_oriented_rect_rected, PolygonSmall, ShapeCommon, BoxAligned, ShapeClosest, ClosestPoint};
 
 pub struct BoxOrientedBoxy(PolygonSmall);
 
@@ -37,3 +37,9 @@ impl ShapeDebug for BoxOrientedBoxy {
         self.0.get_debug_shape_data()
     }
 }
+
+impl ShapeClosest for BoxOrientedBoxy {
+    fn closest_point(&self, point: Vec2) -> ClosestPoint {
+        self.0.closest_point(point)
+    }
+}
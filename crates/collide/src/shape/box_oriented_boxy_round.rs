@@ -2,7 +2,7 @@
 
 use bevy::prelude::Vec2;
 
-use crate::prelude::{RayTarget, RayCaster, RayIntersection, ShapeDebug, ShapeDebugData, PolygonSmallRound, ShapeCommon, BoxAligned};
+use crate::prelude::{RayTarget, RayCaster, RayIntersection, ShapeDebug, ShapeDebugData, PolygonSmallRound, ShapeCommon, BoxAligned, ShapeClosest, ClosestPoint};
 use super::get_polygon_data_for_oriented_rect_rected;
 
 pub struct BoxOrientedBoxyRound(PolygonSmallRound);
@@ -38,3 +38,9 @@ impl ShapeDebug for BoxOrientedBoxyRound {
         self.0.get_debug_shape_data()
     }
 }
+
+impl ShapeClosest for BoxOrientedBoxyRound {
+    fn closest_point(&self, point: Vec2) -> ClosestPoint {
+        self.0.closest_point(point)
+    }
+}
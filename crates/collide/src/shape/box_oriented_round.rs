@@ -2,7 +2,7 @@
 
 use bevy::prelude::Vec2;
 
-use crate::prelude::{RayTarget, RayCaster, RayIntersection, ShapeDebug, ShapeDebugData, BoxAligned, ShapeCommon};
+use crate::prelude::{RayTarget, RayCaster, RayIntersection, ShapeDebug, ShapeDebugData, BoxAligned, ShapeCommon, ShapeClosest, ClosestPoint, find_closest_on_polygon, get_polygon_data_for_rect};
 
 #[derive(Debug, Clone, Copy)]
 pub struct BoxOrientedRound {
@@ -81,3 +81,10 @@ impl ShapeDebug for BoxOrientedRound {
         )
     }
 }
+
+impl ShapeClosest for BoxOrientedRound {
+    fn closest_point(&self, point: Vec2) -> ClosestPoint {
+        let (points, normals, lengths) = get_polygon_data_for_rect(self.size, self.direction);
+        find_closest_on_polygon(self.origin, &points, &normals, &lengths, self.radius, point)
+    }
+}
@@ -0,0 +1,79 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use bevy::prelude::Vec2;
+
+use crate::prelude::{RayTarget, RayCaster, RayIntersection, ShapeDebug, ShapeDebugData, ShapeCommon, BoxAligned, ShapeClosest, ClosestPoint, find_closest_on_polygon};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Capsule {
+    pub origin:    Vec2,
+    pub direction: Vec2,
+    pub length:    f32,
+    pub radius:    f32,
+}
+
+impl Capsule {
+    pub fn new(origin: Vec2, direction: Vec2, length: f32, radius: f32) -> Self {
+        Self{origin, direction, length, radius}
+    }
+
+    pub fn new_vertical(origin: Vec2, height: f32, radius: f32) -> Self {
+        Self::new(origin, Vec2::Y, (height - 2.0*radius).max(0.0), radius)
+    }
+
+    pub fn get_points(&self) -> [Vec2; 2] {
+        let offset = self.direction*self.length*0.5;
+        [
+            self.origin - offset,
+            self.origin + offset,
+        ]
+    }
+
+    fn get_polygon_data(&self) -> ([Vec2; 2], [Vec2; 2], [f32; 2]) {
+        (
+            self.get_points(),
+            [-self.direction.perp(), self.direction.perp()],
+            [self.length, self.length],
+        )
+    }
+}
+
+impl ShapeCommon for Capsule {
+    fn bounding_box(&self) -> BoxAligned {
+        let size = (self.direction*self.length*0.5).abs();
+        BoxAligned::new(self.origin, Vec2::new(size.x + self.radius, size.y + self.radius))
+    }
+
+    fn origin(&self) -> Vec2 {
+        self.origin
+    }
+
+    fn set_origin(&mut self, origin: Vec2) {
+        self.origin = origin;
+    }
+}
+
+impl RayTarget for Capsule {
+    fn raycast(&self, ray: &RayCaster) -> Option<[RayIntersection; 2]> {
+        let (points, normals, lengths) = self.get_polygon_data();
+        ray.test_polygon_rounded_at_origin(&points, &normals, &lengths, self.radius)
+    }
+}
+
+impl ShapeDebug for Capsule {
+    fn get_debug_shape_data(&self) -> ShapeDebugData {
+        let (points, normals, _lengths) = self.get_polygon_data();
+        ShapeDebugData::polygon_round(
+            Box::new(points),
+            Box::new(normals),
+            self.radius,
+        )
+    }
+}
+
+impl ShapeClosest for Capsule {
+    fn closest_point(&self, point: Vec2) -> ClosestPoint {
+        let (points, normals, lengths) = self.get_polygon_data();
+        find_closest_on_polygon(Vec2::ZERO, &points, &normals, &lengths, self.radius, point)
+    }
+}
@@ -0,0 +1,240 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use std::sync::Arc;
+
+use bevy::prelude::Vec2;
+
+use crate::prelude::{RayTarget, ShapeDebug, RayCaster, RayIntersection, ShapeDebugData, BoxAligned, ShapeCommon, ShapeClosest, ClosestPoint, get_polygon_data_for_points, get_corners_for_points, find_closest_on_edges};
+
+// NOTE Chains are one-sided and wound like polygons, so the solid side is on
+//      the left as you walk along them (ie. ground is walked right to left).
+//      The ghost vertices are the points either side of the chain that belong
+//      to its neighbours, they're only used to figure out if the end points
+//      are corners, so things can slide across the joins without snagging.
+//      The points are shared, so chains are cheap to clone into queries.
+
+#[derive(Debug, Clone)]
+pub struct EdgeChain {
+    pub(super) points:  Arc<[Vec2]>,
+    pub(super) normals: Arc<[Vec2]>,
+    pub(super) lengths: Arc<[ f32]>,
+    pub(super) corners: Arc<[bool]>,
+    pub(super) ghosts:  [Option<Vec2>; 2],
+    pub(super) bounds:  BoxAligned,
+}
+
+impl EdgeChain {
+    pub fn new(points: impl IntoIterator<Item = Vec2>, ghost_prev: Option<Vec2>, ghost_next: Option<Vec2>) -> Self {
+        let points: Arc<[Vec2]> = points.into_iter().collect();
+        assert!(points.len() >= 2, "Chain has too few points, min 2");
+
+        let (normals, lengths, bounds) = get_polygon_data_for_points(&points, false);
+        let corners = get_corners_for_points(&points, ghost_prev, ghost_next);
+        Self{points, normals: normals.into(), lengths: lengths.into(), corners: corners.into(), ghosts: [ghost_prev, ghost_next], bounds}
+    }
+
+    pub fn try_new(points: impl IntoIterator<Item = Vec2>, ghost_prev: Option<Vec2>, ghost_next: Option<Vec2>) -> Result<Self, &'static str> {
+        let points: Vec<Vec2> = points.into_iter().collect();
+        if points.len() < 2 {
+            return Err("Chain has too few points, min 2");
+        }
+
+        if points.iter().chain(ghost_prev.iter()).chain(ghost_next.iter()).any(|v| !v.is_finite()) {
+            return Err("Chain has a point that isn't finite");
+        }
+
+        if points.windows(2).any(|v| v[0] == v[1]) {
+            return Err("Chain has a zero length edge");
+        }
+
+        Ok(Self::new(points, ghost_prev, ghost_next))
+    }
+
+    pub fn points(&self) -> &[Vec2] {
+        &self.points
+    }
+
+    pub fn ghosts(&self) -> [Option<Vec2>; 2] {
+        self.ghosts
+    }
+
+    pub fn is_corner(&self, idx: usize) -> bool {
+        self.corners[idx]
+    }
+}
+
+impl ShapeCommon for EdgeChain {
+    fn bounding_box(&self) -> BoxAligned {
+        self.bounds
+    }
+
+    fn origin(&self) -> Vec2 {
+        self.bounds.origin
+    }
+
+    fn set_origin(&mut self, origin: Vec2) {
+        let delta = origin - self.bounds.origin;
+        self.points = self.points.iter().map(|&v| v + delta).collect();
+        self.ghosts = self.ghosts.map(|v| v.map(|v| v + delta));
+        self.bounds.origin = origin;
+    }
+}
+
+impl RayTarget for EdgeChain {
+    fn raycast(&self, ray: &RayCaster) -> Option<[RayIntersection; 2]> {
+        // There's no inside to a chain, so we enter and exit at the same point
+        self.raycast_enter(ray).map(|v| [v, v])
+    }
+
+    fn raycast_enter(&self, ray: &RayCaster) -> Option<RayIntersection> {
+        (0..self.lengths.len())
+            .filter(|&i| ray.direction().dot(self.normals[i]) < 0.0)
+            .filter_map(|i| ray
+                .test_line_opt(self.points[i], self.normals[i].perp(), self.lengths[i])
+                .map(|v| RayIntersection{normal: self.normals[i], ..v})
+            )
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
+
+    fn raycast_exit(&self, ray: &RayCaster) -> Option<RayIntersection> {
+        self.raycast_enter(ray)
+    }
+}
+
+impl ShapeDebug for EdgeChain {
+    fn get_debug_shape_data(&self) -> ShapeDebugData {
+        ShapeDebugData::chain(
+            self.points.to_vec().into_boxed_slice(),
+            self.normals.to_vec().into_boxed_slice(),
+        )
+    }
+}
+
+impl ShapeClosest for EdgeChain {
+    fn closest_point(&self, point: Vec2) -> ClosestPoint {
+        let (idx, along, closest) = find_closest_on_edges(&self.points, &self.normals, &self.lengths, point);
+        let offset = point - closest;
+        let length = offset.length();
+
+        // The chain has no thickness, so being behind it is just as outside as
+        // being in front of it. Shapes swept against it still overlap from the
+        // back, but only as deep as they are, see EdgeChainMinkowski.
+        let is_behind = self.normals[idx].dot(offset) < 0.0;
+
+        // Only corners push out along the direction to the vertex, otherwise 
+        // we treat it like the edge continues on, which avoids the snagging.
+        let vertex = if along <= 0.0 { Some(idx) } else if along >= self.lengths[idx] { Some(idx+1) } else { None };
+        let normal = match vertex {
+            Some(vertex) if self.corners[vertex] && length > 0.0 => offset/length,
+            _ if is_behind => -self.normals[idx],
+            _ => self.normals[idx],
+        };
+
+        ClosestPoint{
+            point:    closest,
+            normal,
+            distance: length,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::Vec2;
+    use crate::prelude::{Ball, BoxAligned, EdgeChain, RayCaster, ShapeClosest, ShapeMoving, ShapeStatic};
+
+    #[test]
+    fn check_one_sided() {
+        let chain = EdgeChain::new([Vec2::new(2.0, 0.0), Vec2::new(-2.0, 0.0)], None, None);
+        let [enter, _] = RayCaster::new(Vec2::new(0.0, 1.0), -Vec2::Y).test(&chain).unwrap();
+        assert!((enter.distance - 1.0).abs() < 1.0e-5);
+        assert!((enter.normal - Vec2::Y).length() < 1.0e-5);
+        assert!(RayCaster::new(Vec2::new(0.0, -1.0), Vec2::Y).test(&chain).is_none());
+
+        // Only swept into from the front
+        let chain: ShapeStatic = chain.into();
+        let mover: ShapeMoving = Ball::new(Vec2::new(0.0, 2.0), 0.5).into();
+        let cast = mover.cast_static(-Vec2::Y, 5.0, &chain).unwrap();
+        assert!((cast.distance - 1.5).abs() < 1.0e-5);
+        assert!((cast.normal - Vec2::Y).length() < 1.0e-5);
+
+        let mover: ShapeMoving = Ball::new(Vec2::new(0.0, -2.0), 0.5).into();
+        assert!(mover.cast_static(Vec2::Y, 5.0, &chain).is_none());
+
+        // Anything just behind it is pushed out the front
+        let mover: ShapeMoving = Ball::new(Vec2::new(0.0, -0.3), 0.5).into();
+        let push = mover.find_depenetration_static(&chain).unwrap();
+        assert!((push - Vec2::Y*0.8).length() < 1.0e-4, "{:?}", push);
+
+        // But anything further down than it is deep is left alone
+        let mover: ShapeMoving = Ball::new(Vec2::new(0.0, -3.0), 0.5).into();
+        assert!(mover.find_depenetration_static(&chain).is_none());
+        assert!(mover.overlap_static(&chain).is_none());
+
+        let closest = chain.closest_point(Vec2::new(0.0, -3.0));
+        assert!((closest.distance - 3.0).abs() < 1.0e-5);
+        assert!((closest.normal + Vec2::Y).length() < 1.0e-5);
+    }
+
+    #[test]
+    fn check_try_new() {
+        assert!(EdgeChain::try_new([Vec2::X, -Vec2::X], None, Some(Vec2::Y)).is_ok());
+        assert!(EdgeChain::try_new([Vec2::X], None, None).is_err());
+        assert!(EdgeChain::try_new([], None, None).is_err());
+        assert!(EdgeChain::try_new([Vec2::X, Vec2::X, -Vec2::X], None, None).is_err());
+        assert!(EdgeChain::try_new([Vec2::X, Vec2::new(f32::NAN, 0.0)], None, None).is_err());
+        assert!(EdgeChain::try_new([Vec2::X, -Vec2::X], Some(Vec2::INFINITY), None).is_err());
+    }
+
+    #[test]
+    fn check_seam() {
+        // Two pieces of flat ground that know about each other
+        let [right, left] = [Vec2::new(4.0, 0.0), Vec2::new(-4.0, 0.0)];
+        let ground: ShapeStatic = EdgeChain::new([Vec2::ZERO, left], Some(right), None).into();
+        let ground_open: ShapeStatic = EdgeChain::new([Vec2::ZERO, left], None, None).into();
+
+        // Sliding across from the other piece, just sunk into it
+        let mover: ShapeMoving = BoxAligned::new(Vec2::new(2.0, 0.49), Vec2::splat(0.5)).into();
+        assert!(mover.cast_static(-Vec2::X, 4.0, &ground).is_none());
+
+        let cast = mover.cast_static(-Vec2::X, 4.0, &ground_open).unwrap();
+        assert!((cast.distance - 1.5).abs() < 1.0e-5);
+        assert!((cast.normal - Vec2::X).length() < 1.0e-5);
+
+        // Overlapping the end is pushed up, not back
+        let mover: ShapeMoving = BoxAligned::new(Vec2::new(0.45, 0.4), Vec2::splat(0.5)).into();
+        let overlap = mover.overlap_static(&ground).unwrap();
+        assert!((overlap.normal - Vec2::Y).length() < 1.0e-5);
+        assert!((overlap.depth - 0.1).abs() < 1.0e-4);
+
+        let overlap = mover.overlap_static(&ground_open).unwrap();
+        assert!((overlap.normal - Vec2::X).length() < 1.0e-5);
+
+        // Still lands on it from above
+        let mover: ShapeMoving = BoxAligned::new(Vec2::new(0.2, 3.0), Vec2::splat(0.5)).into();
+        let cast = mover.cast_static(-Vec2::Y, 5.0, &ground).unwrap();
+        assert!((cast.distance - 2.5).abs() < 1.0e-5);
+        assert!((cast.normal - Vec2::Y).length() < 1.0e-5);
+    }
+
+    #[test]
+    fn check_long_chain() {
+        // Stairs going down to the right, only the few edges near the mover matter
+        let points = (0..200).rev().flat_map(|i| {
+            let [x, y] = [i as f32, -(i as f32)];
+            [Vec2::new(x + 1.0, y - 1.0), Vec2::new(x + 1.0, y)]
+        });
+        let ground: ShapeStatic = EdgeChain::new(points, None, None).into();
+
+        let mover: ShapeMoving = BoxAligned::new(Vec2::new(100.5, 2.0), Vec2::splat(0.25)).into();
+        let cast = mover.cast_static(-Vec2::Y, 200.0, &ground).unwrap();
+        assert!((cast.distance - 101.75).abs() < 1.0e-4);
+        assert!((cast.normal - Vec2::Y).length() < 1.0e-5);
+
+        // Sunk into a step, pushed back up out of it
+        let mover: ShapeMoving = Ball::new(Vec2::new(50.5, -49.75), 0.5).into();
+        let overlap = mover.overlap_static(&ground).unwrap();
+        assert!((overlap.depth - 0.25).abs() < 1.0e-4);
+        assert!((overlap.normal - Vec2::Y).length() < 1.0e-5);
+    }
+}
@@ -0,0 +1,213 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use std::sync::Arc;
+
+use bevy::prelude::Vec2;
+
+use tinyvec::ArrayVec;
+
+use crate::prelude::{RayTarget, ShapeDebug, RayCaster, RayIntersection, ShapeDebugData, BoxAligned, ShapeCommon, ShapeClosest, ClosestPoint, EdgeChain, PolygonLarge, PolygonMinkowski, find_closest_on_edges, is_point_in_polygon, POLYGON_SMALL_CAPACITY};
+
+// NOTE The sum with a chain is the union of the sums with each of its edges,
+//      which are built as they're needed. Faces that come from a vertex that
+//      isn't a corner are inside the union, and faces on the back of an edge
+//      can't be reached from the front, so hits on either are ignored. That's
+//      what lets things slide across the joins. If we're inside the chain and
+//      the nearest face is one of those, we push out along the edge instead.
+//      Closed polygons also count everything inside them as overlapping.
+//      Edges are culled against the query by the bounds of their sum first,
+//      so only the few near it are ever summed. Zero length edges are skipped.
+
+#[derive(Debug, Clone)]
+pub struct EdgeChainMinkowski {
+    hull:    ArrayVec<[Vec2; POLYGON_SMALL_CAPACITY]>,
+    radius:  f32,
+    points:  Arc<[Vec2]>,
+    normals: Arc<[Vec2]>,
+    lengths: Arc<[ f32]>,
+    corners: Arc<[bool]>,
+    closed:  bool,
+    extent:  [Vec2; 2],
+    bounds:  BoxAligned,
+}
+
+impl EdgeChainMinkowski {
+    // NOTE The hull must be convex and CCW, same as for PolygonMinkowski
+    pub fn new_from_chain(hull: &[Vec2], radius: f32, chain: &EdgeChain) -> Self {
+        Self{
+            hull:    hull.iter().copied().collect(),
+            radius,
+            points:  chain.points.clone(),
+            normals: chain.normals.clone(),
+            lengths: chain.lengths.clone(),
+            corners: chain.corners.clone(),
+            closed:  false,
+            extent:  get_extent(hull, radius),
+            bounds:  get_bounds_for_sum(hull, radius, chain.bounds),
+        }
+    }
+
+    pub fn new_from_polygon(hull: &[Vec2], radius: f32, polygon: &PolygonLarge) -> Self {
+        Self{
+            hull:    hull.iter().copied().collect(),
+            radius,
+            points:  polygon.points.clone(),
+            normals: polygon.normals.clone(),
+            lengths: polygon.lengths.clone(),
+            corners: polygon.corners.clone(),
+            closed:  true,
+            extent:  get_extent(hull, radius),
+            bounds:  get_bounds_for_sum(hull, radius, polygon.bounds),
+        }
+    }
+
+    fn get_edge(&self, idx: usize) -> [Vec2; 2] {
+        [self.points[idx], self.points[(idx+1)%self.points.len()]]
+    }
+
+    fn get_piece(&self, idx: usize) -> PolygonMinkowski {
+        PolygonMinkowski::new(&self.hull, &self.get_edge(idx), self.radius)
+    }
+
+    // NOTE The bounds of the sum of the hull with just this edge
+    fn get_piece_bounds(&self, idx: usize) -> [Vec2; 2] {
+        let [a, b] = self.get_edge(idx);
+        [a.min(b) + self.extent[0], a.max(b) + self.extent[1]]
+    }
+
+    fn get_edges(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.lengths.len()).filter(|&i| self.lengths[i] > 0.0)
+    }
+
+    fn is_valid_normal(&self, idx: usize, normal: Vec2) -> bool {
+        const EPSILON: f32 = 1.0e-4;
+        let along = normal.dot(self.normals[idx].perp());
+        normal.dot(self.normals[idx]) >= -EPSILON
+            && (along >= -EPSILON || self.corners[idx])
+            && (along <=  EPSILON || self.corners[(idx+1)%self.points.len()])
+    }
+
+    fn find_exit_along_normal(&self, piece: &PolygonMinkowski, idx: usize, point: Vec2) -> ClosestPoint {
+        let normal = self.normals[idx];
+        let depth  = piece.raycast(&RayCaster::new(point, normal)).map_or(0.0, |[_, exit]| exit.distance.max(0.0));
+        ClosestPoint{
+            point:    point + normal*depth,
+            normal,
+            distance: -depth,
+        }
+    }
+}
+
+fn get_extent(hull: &[Vec2], radius: f32) -> [Vec2; 2] {
+    let min = hull.iter().fold(Vec2::MAX, |a, &b| a.min(b)) - Vec2::splat(radius);
+    let max = hull.iter().fold(Vec2::MIN, |a, &b| a.max(b)) + Vec2::splat(radius);
+    [min, max]
+}
+
+fn get_bounds_for_sum(hull: &[Vec2], radius: f32, bounds: BoxAligned) -> BoxAligned {
+    let [min, max] = get_extent(hull, radius);
+    BoxAligned::new(bounds.origin + (min + max)*0.5, bounds.size + (max - min)*0.5)
+}
+
+fn get_distance_to_bounds([min, max]: [Vec2; 2], point: Vec2) -> f32 {
+    (min - point).max(point - max).max(Vec2::ZERO).length()
+}
+
+impl ShapeCommon for EdgeChainMinkowski {
+    fn bounding_box(&self) -> BoxAligned {
+        self.bounds
+    }
+
+    fn origin(&self) -> Vec2 {
+        self.bounds.origin
+    }
+
+    fn set_origin(&mut self, origin: Vec2) {
+        let delta = origin - self.bounds.origin;
+        self.points = self.points.iter().map(|&v| v + delta).collect();
+        self.bounds.origin = origin;
+    }
+}
+
+impl RayTarget for EdgeChainMinkowski {
+    fn raycast(&self, ray: &RayCaster) -> Option<[RayIntersection; 2]> {
+        // Starting inside a polygon counts as already being inside it,
+        // even if we're too deep to be inside any of the edges
+        if self.closed && is_point_in_polygon(&self.points, ray.origin()) {
+            let closest = self.closest_point(ray.origin());
+            let exit    = ray.test_polygon_concave_at_origin(&self.points, &self.normals).map(|[_, v]| v)?;
+            return Some([RayIntersection{
+                distance: closest.distance,
+                point:    ray.origin() + ray.direction()*closest.distance,
+                normal:   closest.normal,
+            }, exit]);
+        }
+
+        // The first edge we hit from the front, or that we're already inside of
+        self.get_edges()
+            .filter(|&i| {
+                let [min, max] = self.get_piece_bounds(i);
+                ray.test_rect(0.5*(min + max), 0.5*(max - min)).is_some_and(|[enter, exit]|
+                    enter.distance <= ray.max_distance() && exit.distance >= ray.min_distance()
+                )
+            })
+            .filter_map(|i| self.get_piece(i).raycast(ray).map(|v| (i, v)))
+            .filter(|&(i, [enter, exit])| exit.distance >= 0.0 && (enter.distance < 0.0 || self.is_valid_normal(i, enter.normal)))
+            .map(|(_, v)| v)
+            .min_by(|[a, _], [b, _]| a.distance.total_cmp(&b.distance))
+    }
+}
+
+impl ShapeDebug for EdgeChainMinkowski {
+    fn get_debug_shape_data(&self) -> ShapeDebugData {
+        let points  = self.points.to_vec().into_boxed_slice();
+        let normals = self.normals.to_vec().into_boxed_slice();
+        if self.closed {
+            ShapeDebugData::polygon(points, normals)
+        } else {
+            ShapeDebugData::chain(points, normals)
+        }
+    }
+}
+
+impl ShapeClosest for EdgeChainMinkowski {
+    fn closest_point(&self, point: Vec2) -> ClosestPoint {
+        // Being inside is always closer, so this finds the deepest edge we're in.
+        // Edges are visited nearest first, so we can stop once they're further
+        // away than what we've found, and only have to visit every edge we're in.
+        let mut edges: Vec<(f32, usize)> = self.get_edges()
+            .map(|i| (get_distance_to_bounds(self.get_piece_bounds(i), point), i))
+            .collect();
+        edges.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        let mut closest = ClosestPoint{
+            point:    self.origin(),
+            normal:   Vec2::Y,
+            distance: f32::INFINITY,
+        };
+        for (bounds_distance, idx) in edges {
+            if bounds_distance > closest.distance.max(0.0) {
+                break;
+            }
+
+            let piece = self.get_piece(idx);
+            let found = piece.closest_point(point);
+            let found = match (self.is_valid_normal(idx, found.normal), found.distance < 0.0) {
+                (true,  _    ) => found,
+                (false, false) => ClosestPoint{normal: self.normals[idx], ..found},
+                (false, true ) => self.find_exit_along_normal(&piece, idx, point),
+            };
+
+            if found.distance < closest.distance {
+                closest = found;
+            }
+        }
+
+        if closest.distance >= 0.0 && self.closed && is_point_in_polygon(&self.points, point) {
+            let (idx, _, _) = find_closest_on_edges(&self.points, &self.normals, &self.lengths, point);
+            return self.find_exit_along_normal(&self.get_piece(idx), idx, point);
+        }
+
+        closest
+    }
+}
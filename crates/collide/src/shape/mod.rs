@@ -5,6 +5,11 @@
 mod ball;
 pub use ball::*;
 
+// // Capsule // //
+
+mod capsule;
+pub use capsule::*;
+
 // // Box Aligned // //
 
 mod box_aligned;
@@ -49,6 +54,23 @@ pub use polygon_small::*;
 mod polygon_small_round;
 pub use polygon_small_round::*;
 
+mod polygon_minkowski;
+pub use polygon_minkowski::*;
+
+mod polygon_large;
+pub use polygon_large::*;
+
+mod polygon_decompose;
+pub use polygon_decompose::*;
+
+// // Chain // //
+
+mod edge_chain;
+pub use edge_chain::*;
+
+mod edge_chain_minkowski;
+pub use edge_chain_minkowski::*;
+
 // // Shape Types // //
 
 mod shape_static;
@@ -60,11 +82,22 @@ pub use shape_moving::*;
 mod shape_combined;
 pub use shape_combined::*;
 
+mod shape_overlap;
+pub use shape_overlap::*;
+
+mod shape_impact;
+pub use shape_impact::*;
+
+mod shape_transform;
+
 // // Misc // //
 
 mod shape_common;
 pub use shape_common::*;
 
+mod shape_closest;
+pub use shape_closest::*;
+
 mod shape_debug;
 pub use shape_debug::*;
 
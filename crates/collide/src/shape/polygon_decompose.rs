@@ -0,0 +1,173 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use bevy::prelude::Vec2;
+
+use crate::prelude::{PolygonSmall, POLYGON_SMALL_CAPACITY, validate_convex_polygon};
+
+pub fn decompose_polygon(points: &[Vec2]) -> Result<Vec<PolygonSmall>, &'static str> {
+    let pieces = triangulate_polygon(points)?;
+    let pieces = merge_convex_pieces(pieces);
+    Ok(pieces.into_iter().map(PolygonSmall::new_from_points).collect())
+}
+
+fn triangulate_polygon(points: &[Vec2]) -> Result<Vec<Vec<Vec2>>, &'static str> {
+    if points.len() < 3 {
+        return Err("Polygon has too few points, min 3");
+    }
+
+    let area: f32 = (0..points.len()).map(|i| points[i].perp_dot(points[(i+1)%points.len()])).sum();
+    if area <= 0.0 {
+        return Err("Polygon is not wound counter-clockwise");
+    }
+
+    // Ear clipping, we keep cutting off convex corners that don't contain 
+    // any of the other points until there's only a triangle left
+    let mut remaining: Vec<usize> = (0..points.len()).collect();
+    let mut result = Vec::with_capacity(points.len() - 2);
+    while remaining.len() > 3 {
+        let len = remaining.len();
+        let get_corner = |i: usize| [(i + len - 1) % len, i, (i + 1) % len].map(|v| remaining[v]);
+        let get_cross  = |[a, b, c]: [Vec2; 3]| (b - a).perp_dot(c - b);
+        let ear = (0..len).find_map(|i| {
+            let [prev, curr, next] = get_corner(i);
+            let [a, b, c] = [points[prev], points[curr], points[next]];
+            let cross = get_cross([a, b, c]);
+            if cross == 0.0 {
+                // Collinear, we can drop it without changing the shape
+                return Some((i, None));
+            }
+
+            // Other points only get in the way if they're inside the ear, or if
+            // they're a reflex corner on its edge, which would poke into it.
+            let is_blocked = (0..len).any(|j| {
+                let v = remaining[j];
+                if v == prev || v == curr || v == next {
+                    return false;
+                }
+                let is_reflex = get_cross(get_corner(j).map(|v| points[v])) < 0.0;
+                is_point_in_triangle(points[v], a, b, c, is_reflex)
+            });
+
+            (cross > 0.0 && !is_blocked).then(|| (i, Some(vec![a, b, c])))
+        });
+
+        let Some((idx, triangle)) = ear else {
+            return Err("Polygon is not simple");
+        };
+
+        result.extend(triangle);
+        remaining.remove(idx);
+    }
+
+    // The last of the collinear points can leave us with a flat triangle
+    let last: Vec<Vec2> = remaining.iter().map(|&v| points[v]).collect();
+    if (last[1] - last[0]).perp_dot(last[2] - last[1]) != 0.0 {
+        result.push(last);
+    }
+    Ok(result)
+}
+
+fn merge_convex_pieces(mut pieces: Vec<Vec<Vec2>>) -> Vec<Vec<Vec2>> {
+    // Greedily remove shared edges while the result is still convex and small enough
+    'outer: loop {
+        for a in 0..pieces.len() {
+            for b in (a+1)..pieces.len() {
+                if let Some(merged) = try_merge_pieces(&pieces[a], &pieces[b]) {
+                    pieces[a] = merged;
+                    pieces.swap_remove(b);
+                    continue 'outer;
+                }
+            }
+        }
+        return pieces;
+    }
+}
+
+fn try_merge_pieces(a: &[Vec2], b: &[Vec2]) -> Option<Vec<Vec2>> {
+    for i in 0..a.len() {
+        let from = a[i];
+        let to   = a[(i+1)%a.len()];
+        let Some(j) = (0..b.len()).find(|&j| b[j] == to && b[(j+1)%b.len()] == from) else {
+            continue;
+        };
+
+        // Walk a from the end of the shared edge back round to its start, then
+        // b from after the shared edge round to just before it.
+        let merged: Vec<Vec2> = (1..=a.len()).map(|k| a[(i+k)%a.len()])
+            .chain((2..b.len()).map(|k| b[(j+k)%b.len()]))
+            .collect();
+
+        let merged = remove_collinear_points(merged);
+        if merged.len() <= POLYGON_SMALL_CAPACITY && validate_convex_polygon(&merged).is_ok() {
+            return Some(merged);
+        }
+    }
+    None
+}
+
+fn remove_collinear_points(mut points: Vec<Vec2>) -> Vec<Vec2> {
+    let mut i = 0;
+    while i < points.len() && points.len() > 3 {
+        let len  = points.len();
+        let prev = points[(i + len - 1) % len];
+        let next = points[(i + 1) % len];
+        if (points[i] - prev).perp_dot(next - points[i]) == 0.0 {
+            points.remove(i);
+        } else {
+            i += 1;
+        }
+    }
+    points
+}
+
+fn is_point_in_triangle(point: Vec2, a: Vec2, b: Vec2, c: Vec2, inclusive: bool) -> bool {
+    let is_inside = |v: f32| if inclusive { v >= 0.0 } else { v > 0.0 };
+    is_inside((b - a).perp_dot(point - a)) && is_inside((c - b).perp_dot(point - b)) && is_inside((a - c).perp_dot(point - c))
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::Vec2;
+    use crate::prelude::{decompose_polygon, validate_convex_polygon, ShapeClosest};
+
+    #[test]
+    fn check_decompose() {
+        // An L shape, which should split into two rectangles
+        let points = [
+            Vec2::new(0.0, 0.0), Vec2::new(2.0, 0.0), Vec2::new(2.0, 1.0),
+            Vec2::new(1.0, 1.0), Vec2::new(1.0, 2.0), Vec2::new(0.0, 2.0),
+        ];
+        let pieces = decompose_polygon(&points).unwrap();
+        assert_eq!(pieces.len(), 2);
+
+        let inside = |p: Vec2| pieces.iter().any(|v| v.contains_point(p));
+        assert!( inside(Vec2::new(0.5, 1.5)));
+        assert!( inside(Vec2::new(1.5, 0.5)));
+        assert!(!inside(Vec2::new(1.5, 1.5)));
+
+        assert_eq!(decompose_polygon(&points.map(|v| -v)).unwrap().len(), 2);
+        assert!(decompose_polygon(&points.into_iter().rev().collect::<Vec<_>>()).is_err());
+    }
+
+    #[test]
+    fn check_decompose_collinear() {
+        // The collinear point is on the edge of the first ear, but doesn't block it
+        let points = [
+            Vec2::new(0.0, 0.0), Vec2::new(4.0, 0.0), Vec2::new(4.0, 1.0), Vec2::new(2.0, 1.0), Vec2::new(0.0, 1.0),
+        ];
+        let pieces = decompose_polygon(&points).unwrap();
+        assert_eq!(pieces.len(), 1);
+        assert_eq!(pieces[0].points().len(), 4);
+
+        // A reflex corner on the edge of an ear does block it, or we'd cover the notch
+        let points = [
+            Vec2::new(0.0, 0.0), Vec2::new(2.0, 0.0), Vec2::new(2.0, 2.0), Vec2::new(1.0, 1.0), Vec2::new(0.0, 2.0),
+        ];
+        let pieces = decompose_polygon(&points).unwrap();
+        let inside = |p: Vec2| pieces.iter().any(|v| v.contains_point(p));
+        assert!(pieces.iter().all(|v| validate_convex_polygon(v.points()).is_ok()));
+        assert!( inside(Vec2::new(0.25, 1.5)));
+        assert!( inside(Vec2::new(1.75, 1.5)));
+        assert!(!inside(Vec2::new(1.0,  1.5)));
+    }
+}
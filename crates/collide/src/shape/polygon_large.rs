@@ -0,0 +1,169 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use std::sync::Arc;
+
+use bevy::prelude::Vec2;
+
+use crate::prelude::{RayTarget, ShapeDebug, RayCaster, RayIntersection, ShapeDebugData, BoxAligned, ShapeCommon, ShapeClosest, ClosestPoint, get_polygon_data_for_points, get_corners_for_points, find_closest_on_edges, is_point_in_polygon};
+
+// NOTE Unlike the small polygons these don't need to be convex, so they
+//      can't be swept against as one sum. Instead they're treated like a closed
+//      edge chain, where only the convex vertices are corners.
+
+#[derive(Debug, Clone)]
+pub struct PolygonLarge {
+    pub(super) points:  Arc<[Vec2]>,
+    pub(super) normals: Arc<[Vec2]>,
+    pub(super) lengths: Arc<[ f32]>,
+    pub(super) corners: Arc<[bool]>,
+    pub(super) bounds:  BoxAligned,
+}
+
+impl PolygonLarge {
+    pub fn new_from_points(points: impl IntoIterator<Item = Vec2>) -> Self {
+        let points: Arc<[Vec2]> = points.into_iter().collect();
+        let (normals, lengths, bounds) = get_polygon_data_for_points(&points, true);
+        let corners = match (points.first(), points.last()) {
+            (Some(&first), Some(&last)) => get_corners_for_points(&points, Some(last), Some(first)),
+            _ => Box::default(),
+        };
+        Self{points, normals: normals.into(), lengths: lengths.into(), corners: corners.into(), bounds}
+    }
+
+    pub fn try_new_from_points(points: impl IntoIterator<Item = Vec2>) -> Result<Self, &'static str> {
+        let result = Self::new_from_points(points);
+        if result.points.len() < 3 {
+            return Err("Polygon has too few points, min 3");
+        }
+
+        if result.lengths.iter().any(|&v| v <= 0.0) {
+            return Err("Polygon has a zero length edge");
+        }
+
+        let area: f32 = (0..result.points.len()).map(|i| result.points[i].perp_dot(result.points[(i+1)%result.points.len()])).sum();
+        if area <= 0.0 {
+            return Err("Polygon is not wound counter-clockwise");
+        }
+
+        Ok(result)
+    }
+
+    pub fn points(&self) -> &[Vec2] {
+        &self.points
+    }
+}
+
+impl ShapeCommon for PolygonLarge {
+    fn bounding_box(&self) -> BoxAligned {
+        self.bounds
+    }
+
+    fn origin(&self) -> Vec2 {
+        self.bounds.origin
+    }
+
+    fn set_origin(&mut self, origin: Vec2) {
+        let delta = origin - self.bounds.origin;
+        self.points = self.points.iter().map(|&v| v + delta).collect();
+        self.bounds.origin = origin;
+    }
+}
+
+impl RayTarget for PolygonLarge {
+    fn raycast(&self, ray: &RayCaster) -> Option<[RayIntersection; 2]> {
+        ray.test_polygon_concave_at_origin(&self.points, &self.normals)
+    }
+}
+
+impl ShapeDebug for PolygonLarge {
+    fn get_debug_shape_data(&self) -> ShapeDebugData {
+        ShapeDebugData::polygon(
+            self.points.to_vec().into_boxed_slice(),
+            self.normals.to_vec().into_boxed_slice(),
+        )
+    }
+}
+
+impl ShapeClosest for PolygonLarge {
+    fn closest_point(&self, point: Vec2) -> ClosestPoint {
+        let (idx, _, closest) = find_closest_on_edges(&self.points, &self.normals, &self.lengths, point);
+        let sign     = if is_point_in_polygon(&self.points, point) { -1.0 } else { 1.0 };
+        let offset   = point - closest;
+        let distance = offset.length();
+        ClosestPoint{
+            point:    closest,
+            normal:   if distance > 0.0 { sign*offset/distance } else { self.normals[idx] },
+            distance: sign*distance,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::Vec2;
+    use crate::prelude::{Ball, BoxAligned, PolygonLarge, RayCaster, ShapeMoving, ShapeStatic};
+
+    fn get_u_shape() -> PolygonLarge {
+        PolygonLarge::try_new_from_points([
+            Vec2::new(0.0, 0.0), Vec2::new(3.0, 0.0), Vec2::new(3.0, 3.0), Vec2::new(2.0, 3.0),
+            Vec2::new(2.0, 1.0), Vec2::new(1.0, 1.0), Vec2::new(1.0, 3.0), Vec2::new(0.0, 3.0),
+        ]).unwrap()
+    }
+
+    #[test]
+    fn check_raycast_concave() {
+        let shape = get_u_shape();
+
+        // In the notch, the arm on the left is behind us
+        let [enter, exit] = RayCaster::new(Vec2::new(1.5, 2.0), Vec2::X).test(&shape).unwrap();
+        assert!((enter.distance - 0.5).abs() < 1.0e-5);
+        assert!((exit.distance  - 1.5).abs() < 1.0e-5);
+        assert!((enter.normal + Vec2::X).length() < 1.0e-5);
+        assert!((exit.normal  - Vec2::X).length() < 1.0e-5);
+
+        let [enter, _] = RayCaster::new(Vec2::new(1.5, 2.0), -Vec2::X).test(&shape).unwrap();
+        assert!((enter.distance - 0.5).abs() < 1.0e-5);
+        assert!((enter.normal - Vec2::X).length() < 1.0e-5);
+        assert!(RayCaster::new_segment(Vec2::new(1.5, 2.0), Vec2::X, 0.0, 0.4).test(&shape).is_none());
+
+        // Starting inside an arm
+        let [enter, exit] = RayCaster::new(Vec2::new(0.5, 2.0), Vec2::X).test(&shape).unwrap();
+        assert!((enter.distance + 0.5).abs() < 1.0e-5);
+        assert!((exit.distance  - 0.5).abs() < 1.0e-5);
+
+        // Along the floor of the notch, the vertices on the ray are only crossed once
+        let [enter, exit] = RayCaster::new(Vec2::new(-1.0, 1.0), Vec2::X).test(&shape).unwrap();
+        assert!((enter.distance - 1.0).abs() < 1.0e-5);
+        assert!((exit.distance  - 2.0).abs() < 1.0e-5);
+    }
+
+    #[test]
+    fn check_sweep_concave() {
+        let shape: ShapeStatic = get_u_shape().into();
+
+        // Dropped into the notch, lands on the bottom of it
+        let mover: ShapeMoving = BoxAligned::new(Vec2::new(1.5, 5.0), Vec2::splat(0.25)).into();
+        let cast = mover.cast_static(-Vec2::Y, 10.0, &shape).unwrap();
+        assert!((cast.distance - 3.75).abs() < 1.0e-5);
+        assert!((cast.normal - Vec2::Y).length() < 1.0e-5);
+        assert!((cast.point - Vec2::new(1.5, 1.0)).length() < 1.0e-5);
+
+        // Too deep to be touching an edge, but still inside
+        let mover: ShapeMoving = Ball::new(Vec2::new(0.5, 0.5), 0.1).into();
+        let overlap = mover.overlap_static(&shape).unwrap();
+        assert!((overlap.depth - 0.6).abs() < 1.0e-4);
+
+        let mover: ShapeMoving = Ball::new(Vec2::new(1.5, 2.0), 0.1).into();
+        assert!(!mover.overlaps_static(&shape));
+    }
+    #[test]
+    fn check_sweep_degenerate() {
+        // Nothing to hit, but shouldn't panic either
+        for points in [vec![], vec![Vec2::ZERO]] {
+            let shape: ShapeStatic = PolygonLarge::new_from_points(points).into();
+            let mover: ShapeMoving = Ball::new(Vec2::new(0.0, 0.5), 1.0).into();
+            assert!(mover.overlap_static(&shape).is_none());
+            assert!(mover.cast_static(-Vec2::Y, 10.0, &shape).is_none());
+        }
+    }
+}
@@ -0,0 +1,126 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use bevy::prelude::Vec2;
+
+use tinyvec::ArrayVec;
+
+use crate::prelude::{RayTarget, ShapeDebug, RayCaster, RayIntersection, ShapeDebugData, BoxAligned, ShapeCommon, ShapeClosest, ClosestPoint, find_closest_on_polygon, get_polygon_points_for_minkowski, POLYGON_SMALL_CAPACITY};
+
+pub const POLYGON_MINKOWSKI_CAPACITY: usize = 2*POLYGON_SMALL_CAPACITY;
+
+#[derive(Debug, Clone, Copy)]
+pub struct PolygonMinkowski {
+    points:  ArrayVec<[Vec2; POLYGON_MINKOWSKI_CAPACITY]>,
+    normals: ArrayVec<[Vec2; POLYGON_MINKOWSKI_CAPACITY]>,
+    lengths: ArrayVec<[ f32; POLYGON_MINKOWSKI_CAPACITY]>,
+    bounds:  BoxAligned,
+    radius:  f32,
+}
+
+impl PolygonMinkowski {
+    // NOTE Both point lists must be convex and CCW, a single point or segment is fine
+    pub fn new(a: &[Vec2], b: &[Vec2], radius: f32) -> Self {
+        let points = get_polygon_points_for_minkowski(a, b);
+        let mut normals: ArrayVec<[Vec2; POLYGON_MINKOWSKI_CAPACITY]> = Default::default();
+        let mut lengths: ArrayVec<[ f32; POLYGON_MINKOWSKI_CAPACITY]> = Default::default();
+
+        let mut min = Vec2::MAX;
+        let mut max = Vec2::MIN;
+
+        for i in 0..points.len() {
+            let start = points[i];
+            min = min.min(start);
+            max = max.max(start);
+
+            // Single points have no edges, give them something sensible so
+            // the closest point queries still work as a circle
+            let end    = points[(i+1)%points.len()];
+            let offset = end-start;
+            let length = offset.length();
+            let normal = if length > 0.0 { -(offset/length).perp() } else { Vec2::Y };
+            lengths.push(length);
+            normals.push(normal);
+        }
+
+        let origin = (min + max)*0.5;
+        let size   = max - origin;
+        Self{points, normals, lengths, bounds: BoxAligned::new(origin, size), radius}
+    }
+}
+
+impl ShapeCommon for PolygonMinkowski {
+    fn bounding_box(&self) -> BoxAligned {
+        let mut bounds = self.bounds;
+        bounds.size += Vec2::new(self.radius, self.radius);
+        bounds
+    }
+
+    fn origin(&self) -> Vec2 {
+        self.bounds.origin
+    }
+
+    fn set_origin(&mut self, origin: Vec2) {
+        let delta = origin - self.bounds.origin;
+        for point in self.points.iter_mut() {
+            *point += delta;
+        }
+        self.bounds.origin = origin;
+    }
+}
+
+impl RayTarget for PolygonMinkowski {
+    fn raycast(&self, ray: &RayCaster) -> Option<[RayIntersection; 2]> {
+        if self.radius > 0.0 {
+            ray.test_polygon_rounded_at_origin(&self.points, &self.normals, &self.lengths, self.radius)
+        } else {
+            ray.test_polygon_at_origin(&self.points, &self.normals, &self.lengths)
+        }
+    }
+}
+
+impl ShapeDebug for PolygonMinkowski {
+    fn get_debug_shape_data(&self) -> ShapeDebugData {
+        ShapeDebugData::polygon_round(  
+            self.points.to_vec().into_boxed_slice(),
+            self.normals.to_vec().into_boxed_slice(),
+            self.radius,
+        )
+    }
+}
+
+impl ShapeClosest for PolygonMinkowski {
+    fn closest_point(&self, point: Vec2) -> ClosestPoint {
+        find_closest_on_polygon(Vec2::ZERO, &self.points, &self.normals, &self.lengths, self.radius, point)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::Vec2;
+    use crate::prelude::{PolygonMinkowski, ShapeCommon, PolygonSmall, ShapeMoving, BoxAligned, ShapeStatic};
+
+    #[test]
+    fn check_minkowski_rects() {
+        let a = [Vec2::new(1.0, 1.0), Vec2::new(-1.0, 1.0), Vec2::new(-1.0, -1.0), Vec2::new(1.0, -1.0)];
+        let b = a.map(|v| v*2.0 + Vec2::new(5.0, 0.0));
+        let sum = PolygonMinkowski::new(&a, &b, 0.0);
+        assert_eq!(sum.points.len(), 4);
+        assert!((sum.bounding_box().size - Vec2::splat(3.0)).length() < 1.0e-5);
+        assert!((sum.origin() - Vec2::new(5.0, 0.0)).length() < 1.0e-5);
+    }
+
+    #[test]
+    fn check_minkowski_reflected() {
+        // Triangle pointing down, sitting just above a box
+        let mover: ShapeMoving = PolygonSmall::new_from_points([
+            Vec2::new(0.0, 1.0), Vec2::new(-1.0, 2.0), Vec2::new(1.0, 2.0)
+        ].into_iter().rev()).into();
+        let ground: ShapeStatic = BoxAligned::new(Vec2::ZERO, Vec2::splat(0.5)).into();
+        assert!(!mover.overlaps_static(&ground));
+
+        let mut mover = mover;
+        mover.set_origin(mover.origin() - Vec2::Y*0.75);
+        let push = mover.find_depenetration_static(&ground).unwrap();
+        assert!((push - Vec2::Y*0.25).length() < 1.0e-5);
+    }
+}
@@ -4,10 +4,11 @@ use bevy::prelude::Vec2;
 
 use tinyvec::ArrayVec;
 
-use crate::prelude::{RayTarget, ShapeDebug, RayCaster, RayIntersection, ShapeDebugData, BoxAligned, ShapeCommon};
+use crate::prelude::{RayTarget, ShapeDebug, RayCaster, RayIntersection, ShapeDebugData, BoxAligned, ShapeCommon, ShapeClosest, ClosestPoint, find_closest_on_polygon};
 
 pub const POLYGON_SMALL_CAPACITY: usize = 8;
 
+#[derive(Debug, Clone, Copy)]
 pub struct PolygonSmall {
     pub(super) points:  ArrayVec<[Vec2; POLYGON_SMALL_CAPACITY]>,
     pub(super) normals: ArrayVec<[Vec2; POLYGON_SMALL_CAPACITY]>,
@@ -25,6 +26,8 @@ impl PolygonSmall {
         }
     }
 
+    // NOTE Unchecked, prefer try_new_from_points for points that come from
+    //      outside the code, like level data.
     pub fn new_from_points(points: impl IntoIterator<Item = Vec2>) -> Self {
         let points:      ArrayVec<[Vec2; POLYGON_SMALL_CAPACITY]> = ArrayVec::from_iter(points);
         let mut normals: ArrayVec<[Vec2; POLYGON_SMALL_CAPACITY]> = Default::default();
@@ -50,6 +53,50 @@ impl PolygonSmall {
         let size   = max - origin;
         Self{points, normals, lengths, bounds: BoxAligned::new(origin, size)}
     }
+
+    pub fn try_new_from_points(points: impl IntoIterator<Item = Vec2>) -> Result<Self, &'static str> {
+        let mut checked: ArrayVec<[Vec2; POLYGON_SMALL_CAPACITY]> = Default::default();
+        for point in points {
+            if checked.try_push(point).is_some() {
+                return Err("Polygon has too many points, max 8");
+            }
+        }
+        validate_convex_polygon(&checked)?;
+        Ok(Self::new_from_points(checked))
+    }
+
+    pub fn points(&self) -> &[Vec2] {
+        &self.points
+    }
+}
+
+pub fn validate_convex_polygon(points: &[Vec2]) -> Result<(), &'static str> {
+    if points.len() < 3 {
+        return Err("Polygon has too few points, min 3");
+    }
+
+    let mut area = 0.0;
+    for i in 0..points.len() {
+        let prev = points[i];
+        let curr = points[(i+1)%points.len()];
+        let next = points[(i+2)%points.len()];
+
+        if curr == prev {
+            return Err("Polygon has a zero length edge");
+        }
+
+        if (curr - prev).perp_dot(next - curr) < 0.0 {
+            return Err("Polygon is not convex, or is not wound counter-clockwise");
+        }
+
+        area += prev.perp_dot(curr);
+    }
+
+    if area <= 0.0 {
+        return Err("Polygon is not wound counter-clockwise");
+    }
+
+    Ok(())
 }
 
 impl ShapeCommon for PolygonSmall {
@@ -62,7 +109,7 @@ impl ShapeCommon for PolygonSmall {
     }
 
     fn set_origin(&mut self, origin: Vec2) {
-        let delta = self.bounds.origin - origin;
+        let delta = origin - self.bounds.origin;
         for point in self.points.iter_mut() {
             *point += delta;
         }
@@ -84,3 +131,27 @@ impl ShapeDebug for PolygonSmall {
         )
     }
 }
+
+impl ShapeClosest for PolygonSmall {
+    fn closest_point(&self, point: Vec2) -> ClosestPoint {
+        find_closest_on_polygon(Vec2::ZERO, &self.points, &self.normals, &self.lengths, 0.0, point)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::Vec2;
+    use crate::prelude::PolygonSmall;
+
+    #[test]
+    fn check_validation() {
+        let square = [Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0), Vec2::new(1.0, 1.0), Vec2::new(0.0, 1.0)];
+        assert!(PolygonSmall::try_new_from_points(square).is_ok());
+        assert!(PolygonSmall::try_new_from_points(square.into_iter().rev()).is_err());
+        assert!(PolygonSmall::try_new_from_points([Vec2::ZERO, Vec2::X]).is_err());
+        assert!(PolygonSmall::try_new_from_points([
+            Vec2::new(0.0, 0.0), Vec2::new(2.0, 0.0), Vec2::new(1.0, 0.5), Vec2::new(2.0, 2.0), Vec2::new(0.0, 2.0)
+        ]).is_err());
+        assert!(PolygonSmall::try_new_from_points((0..9).map(|i| Vec2::from_angle(i as f32))).is_err());
+    }
+}
@@ -2,11 +2,12 @@
 
 use bevy::prelude::Vec2;
 
-use crate::prelude::{RayTarget, ShapeDebug, RayCaster, RayIntersection, ShapeDebugData, PolygonSmall, BoxAligned, ShapeCommon};
+use crate::prelude::{RayTarget, ShapeDebug, RayCaster, RayIntersection, ShapeDebugData, PolygonSmall, BoxAligned, ShapeCommon, ShapeClosest, ClosestPoint, find_closest_on_polygon};
 
+#[derive(Debug, Clone, Copy)]
 pub struct PolygonSmallRound {
-    inner: PolygonSmall,
-    radius: f32,
+    pub(super) inner: PolygonSmall,
+    pub(super) radius: f32,
 }
 
 impl PolygonSmallRound {
@@ -17,6 +18,18 @@ impl PolygonSmallRound {
     pub fn new_from_points(points: impl IntoIterator<Item = Vec2>, radius: f32) -> Self {
         Self::new(PolygonSmall::new_from_points(points), radius)
     }
+
+    pub fn try_new_from_points(points: impl IntoIterator<Item = Vec2>, radius: f32) -> Result<Self, &'static str> {
+        PolygonSmall::try_new_from_points(points).map(|v| Self::new(v, radius))
+    }
+
+    pub fn inner(&self) -> &PolygonSmall {
+        &self.inner
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
 }
 
 impl ShapeCommon for PolygonSmallRound {
@@ -50,3 +63,9 @@ impl ShapeDebug for PolygonSmallRound {
         )
     }
 }
+
+impl ShapeClosest for PolygonSmallRound {
+    fn closest_point(&self, point: Vec2) -> ClosestPoint {
+        find_closest_on_polygon(Vec2::ZERO, &self.inner.points, &self.inner.normals, &self.inner.lengths, self.radius, point)
+    }
+}
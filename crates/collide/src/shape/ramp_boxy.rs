@@ -2,7 +2,7 @@
 
 use bevy::prelude::Vec2;
 
-use crate::prelude::{RayTarget, RayCaster, RayIntersection, ShapeDebug, ShapeDebugData, get_polygon_data_for_ramp_boxy, PolygonSmall, BoxAligned, ShapeCommon};
+use crate::prelude::{RayTarget, RayCaster, RayIntersection, ShapeDebug, ShapeDebugData, get_polygon_data_for_ramp_boxy, PolygonSmall, BoxAligned, ShapeCommon, ShapeClosest, ClosestPoint};
 
 pub struct RampBoxy(PolygonSmall);
 
@@ -43,3 +43,9 @@ impl ShapeDebug for RampBoxy {
         self.0.get_debug_shape_data()
     }
 }
+
+impl ShapeClosest for RampBoxy {
+    fn closest_point(&self, point: Vec2) -> ClosestPoint {
+        self.0.closest_point(point)
+    }
+}
@@ -2,7 +2,7 @@
 
 use bevy::prelude::Vec2;
 
-use crate::prelude::{RayTarget, RayCaster, RayIntersection, ShapeDebugData, ShapeDebug, get_polygon_data_for_ramp_boxy, PolygonSmallRound, PolygonSmall, BoxAligned, ShapeCommon};
+use crate::prelude::{RayTarget, RayCaster, RayIntersection, ShapeDebugData, ShapeDebug, get_polygon_data_for_ramp_boxy, PolygonSmallRound, PolygonSmall, BoxAligned, ShapeCommon, ShapeClosest, ClosestPoint};
 
 pub struct RampBoxyRound(PolygonSmallRound);
 
@@ -43,3 +43,9 @@ impl ShapeDebug for RampBoxyRound {
         self.0.get_debug_shape_data()
     }
 }
+
+impl ShapeClosest for RampBoxyRound {
+    fn closest_point(&self, point: Vec2) -> ClosestPoint {
+        self.0.closest_point(point)
+    }
+}
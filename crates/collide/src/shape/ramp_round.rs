@@ -2,7 +2,7 @@
 
 use bevy::prelude::Vec2;
 
-use crate::prelude::{RayTarget, RayCaster, RayIntersection, ShapeDebug, ShapeDebugData, get_polygon_data_for_ramp, ShapeCommon, BoxAligned};
+use crate::prelude::{RayTarget, RayCaster, RayIntersection, ShapeDebug, ShapeDebugData, get_polygon_data_for_ramp, ShapeCommon, BoxAligned, ShapeClosest, ClosestPoint, find_closest_on_polygon};
 
 #[derive(Debug, Clone, Copy)]
 pub struct RampRound {
@@ -59,3 +59,10 @@ impl ShapeDebug for RampRound {
         )
     }
 }
+
+impl ShapeClosest for RampRound {
+    fn closest_point(&self, point: Vec2) -> ClosestPoint {
+        let (points, normals, lengths) = get_polygon_data_for_ramp(self.direction, self.length);
+        find_closest_on_polygon(self.origin, &points, &normals, &lengths, self.radius, point)
+    }
+}
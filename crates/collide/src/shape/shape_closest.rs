@@ -0,0 +1,57 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use bevy::prelude::Vec2;
+
+#[derive(Debug, Clone, Copy)]
+pub struct ClosestPoint {
+    pub point:    Vec2,
+    pub normal:   Vec2,
+    pub distance: f32,
+}
+
+pub trait ShapeClosest {
+    fn closest_point(&self, point: Vec2) -> ClosestPoint;
+
+    fn signed_distance(&self, point: Vec2) -> f32 {
+        self.closest_point(point).distance
+    }
+
+    fn contains_point(&self, point: Vec2) -> bool {
+        self.signed_distance(point) <= 0.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::Vec2;
+    use crate::prelude::{Ramp, ShapeClosest, BoxOriented, Ball, BoxAlignedRound, ShapeStatic};
+
+    #[test]
+    fn check_contains_point() {
+        let ramp: ShapeStatic = Ramp::new_from_size_centered(Vec2::ZERO, Vec2::ONE, Vec2::ONE).into();
+        assert!( ramp.contains_point(Vec2::new(-0.25, -0.25)));
+        assert!(!ramp.contains_point(Vec2::new( 0.25,  0.25)));
+
+        let rect: ShapeStatic = BoxOriented::new(Vec2::ZERO, Vec2::new(1.0, 0.1), Vec2::new(1.0, 1.0).normalize()).into();
+        assert!( rect.contains_point(Vec2::new( 0.5,  0.5)));
+        assert!(!rect.contains_point(Vec2::new( 0.5, -0.5)));
+
+        let ball: ShapeStatic = Ball::new(Vec2::new(5.0, 0.0), 1.0).into();
+        assert!( ball.contains_point(Vec2::new(5.5, 0.5)));
+        assert!(!ball.contains_point(Vec2::ZERO));
+    }
+
+    #[test]
+    fn check_closest_point() {
+        let ramp = Ramp::new_from_size_centered(Vec2::ZERO, Vec2::ONE, Vec2::ONE);
+        let closest = ramp.closest_point(Vec2::new(0.5, 0.5));
+        assert!(closest.point.length() < 1.0e-5);
+        assert!((closest.normal - Vec2::ONE.normalize()).length() < 1.0e-5);
+        assert!((closest.distance - std::f32::consts::FRAC_1_SQRT_2).abs() < 1.0e-5);
+
+        let rect = BoxAlignedRound::new(Vec2::ZERO, Vec2::ONE, 0.5);
+        let closest = rect.closest_point(Vec2::new(2.0, 2.0));
+        assert!((closest.point - (Vec2::ONE + Vec2::ONE.normalize()*0.5)).length() < 1.0e-5);
+        assert!((rect.signed_distance(Vec2::new(0.0, 0.5)) + 1.0).abs() < 1.0e-5);
+    }
+}
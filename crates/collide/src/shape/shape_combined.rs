@@ -3,6 +3,7 @@
 use bevy::math::Vec2;
 use macro_attr_2018::macro_attr;
 use enum_derive_2018::EnumFromInner;
+use tinyvec::ArrayVec;
 
 use crate::prelude::*;
 
@@ -21,6 +22,11 @@ macro_attr! {
         RampRound(RampRound),
         RampBoxy(RampBoxy),
         RampBoxyRound(RampBoxyRound),
+
+        Capsule(Capsule),
+        PolygonSmallRound(PolygonSmallRound),
+        PolygonMinkowski(PolygonMinkowski),
+        EdgeChainMinkowski(EdgeChainMinkowski),
     }
 }
 
@@ -36,6 +42,10 @@ impl ShapeCommon for ShapeCombined {
             ShapeCombined::RampRound(s) => s.bounding_box(),
             ShapeCombined::RampBoxy(s) => s.bounding_box(),
             ShapeCombined::RampBoxyRound(s) => s.bounding_box(),
+            ShapeCombined::Capsule(s) => s.bounding_box(),
+            ShapeCombined::PolygonSmallRound(s) => s.bounding_box(),
+            ShapeCombined::PolygonMinkowski(s) => s.bounding_box(),
+            ShapeCombined::EdgeChainMinkowski(s) => s.bounding_box(),
         }
     }
 
@@ -50,6 +60,10 @@ impl ShapeCommon for ShapeCombined {
             ShapeCombined::RampRound(s) => s.origin(),
             ShapeCombined::RampBoxy(s) => s.origin(),
             ShapeCombined::RampBoxyRound(s) => s.origin(),
+            ShapeCombined::Capsule(s) => s.origin(),
+            ShapeCombined::PolygonSmallRound(s) => s.origin(),
+            ShapeCombined::PolygonMinkowski(s) => s.origin(),
+            ShapeCombined::EdgeChainMinkowski(s) => s.origin(),
         }
     }
 
@@ -64,6 +78,10 @@ impl ShapeCommon for ShapeCombined {
             ShapeCombined::RampRound(s) => s.set_origin(origin),
             ShapeCombined::RampBoxy(s) => s.set_origin(origin),
             ShapeCombined::RampBoxyRound(s) => s.set_origin(origin),
+            ShapeCombined::Capsule(s) => s.set_origin(origin),
+            ShapeCombined::PolygonSmallRound(s) => s.set_origin(origin),
+            ShapeCombined::PolygonMinkowski(s) => s.set_origin(origin),
+            ShapeCombined::EdgeChainMinkowski(s) => s.set_origin(origin),
         }
     }
 }
@@ -80,6 +98,10 @@ impl ShapeDebug for ShapeCombined {
             ShapeCombined::RampRound(s) => s.get_debug_shape_data(),
             ShapeCombined::RampBoxy(s) => s.get_debug_shape_data(),
             ShapeCombined::RampBoxyRound(s) => s.get_debug_shape_data(),
+            ShapeCombined::Capsule(s) => s.get_debug_shape_data(),
+            ShapeCombined::PolygonSmallRound(s) => s.get_debug_shape_data(),
+            ShapeCombined::PolygonMinkowski(s) => s.get_debug_shape_data(),
+            ShapeCombined::EdgeChainMinkowski(s) => s.get_debug_shape_data(),
         }
     }
 }
@@ -96,6 +118,10 @@ impl RayTarget for ShapeCombined {
             ShapeCombined::RampRound(s) => s.raycast(ray),
             ShapeCombined::RampBoxy(s) => s.raycast(ray),
             ShapeCombined::RampBoxyRound(s) => s.raycast(ray),
+            ShapeCombined::Capsule(s) => s.raycast(ray),
+            ShapeCombined::PolygonSmallRound(s) => s.raycast(ray),
+            ShapeCombined::PolygonMinkowski(s) => s.raycast(ray),
+            ShapeCombined::EdgeChainMinkowski(s) => s.raycast(ray),
         }
     }
 
@@ -110,6 +136,10 @@ impl RayTarget for ShapeCombined {
             ShapeCombined::RampRound(s) => s.raycast_enter(ray),
             ShapeCombined::RampBoxy(s) => s.raycast_enter(ray),
             ShapeCombined::RampBoxyRound(s) => s.raycast_enter(ray),
+            ShapeCombined::Capsule(s) => s.raycast_enter(ray),
+            ShapeCombined::PolygonSmallRound(s) => s.raycast_enter(ray),
+            ShapeCombined::PolygonMinkowski(s) => s.raycast_enter(ray),
+            ShapeCombined::EdgeChainMinkowski(s) => s.raycast_enter(ray),
         }
     }
 
@@ -124,6 +154,30 @@ impl RayTarget for ShapeCombined {
             ShapeCombined::RampRound(s) => s.raycast_exit(ray),
             ShapeCombined::RampBoxy(s) => s.raycast_exit(ray),
             ShapeCombined::RampBoxyRound(s) => s.raycast_exit(ray),
+            ShapeCombined::Capsule(s) => s.raycast_exit(ray),
+            ShapeCombined::PolygonSmallRound(s) => s.raycast_exit(ray),
+            ShapeCombined::PolygonMinkowski(s) => s.raycast_exit(ray),
+            ShapeCombined::EdgeChainMinkowski(s) => s.raycast_exit(ray),
+        }
+    }
+}
+
+impl ShapeClosest for ShapeCombined {
+    fn closest_point(&self, point: Vec2) -> ClosestPoint {
+        match self {
+            ShapeCombined::Ball(s) => s.closest_point(point),
+            ShapeCombined::BoxAligned(s) => s.closest_point(point),
+            ShapeCombined::BoxAlignedRound(s) => s.closest_point(point),
+            ShapeCombined::BoxOrientedRound(s) => s.closest_point(point),
+            ShapeCombined::BoxOrientedBoxy(s) => s.closest_point(point),
+            ShapeCombined::BoxOrientedBoxyRound(s) =>s.closest_point(point),
+            ShapeCombined::RampRound(s) => s.closest_point(point),
+            ShapeCombined::RampBoxy(s) => s.closest_point(point),
+            ShapeCombined::RampBoxyRound(s) => s.closest_point(point),
+            ShapeCombined::Capsule(s) => s.closest_point(point),
+            ShapeCombined::PolygonSmallRound(s) => s.closest_point(point),
+            ShapeCombined::PolygonMinkowski(s) => s.closest_point(point),
+            ShapeCombined::EdgeChainMinkowski(s) => s.closest_point(point),
         }
     }
 }
@@ -132,6 +186,9 @@ impl ShapeCombined {
 
     pub fn between_moving_and_static(a: &ShapeMoving, b: &ShapeStatic) -> Self {
         match (a, b) {
+            (_, ShapeStatic::PolygonLarge(b)) => Self::between_polygon_large(a, b),
+            (_, ShapeStatic::EdgeChain(b)   ) => Self::between_edge_chain(a, b),
+
             (ShapeMoving::Ball(a),       ShapeStatic::Ball(b)           ) => Ball::new(b.origin, a.radius+b.radius).into(),
             (ShapeMoving::Ball(a),       ShapeStatic::BoxAligned(b)     ) => BoxAlignedRound::new(b.origin, b.size, a.radius).into(),
             (ShapeMoving::Ball(a),       ShapeStatic::BoxAlignedRound(b)) => BoxAlignedRound::new(b.origin, b.size, b.radius + a.radius).into(),
@@ -150,6 +207,23 @@ impl ShapeCombined {
             (ShapeMoving::Ball(a),       ShapeStatic::RampRound(b)) => RampRound::new(b.origin, b.direction, b.length, b.radius + a.radius).into(),
             (ShapeMoving::BoxAligned(a), ShapeStatic::Ramp(b)     ) => RampBoxy::new(b.origin + b.get_normal().signum()*a.size, b.direction, b.length, a.size).into(),
             (ShapeMoving::BoxAligned(a), ShapeStatic::RampRound(b)) => RampBoxyRound::new(b.origin + b.get_normal().signum()*a.size, b.direction, b.length, a.size, b.radius).into(),
+
+            (ShapeMoving::BoxOriented(a), ShapeStatic::Ball(b)           ) => BoxOrientedRound::new(b.origin, a.size, a.direction, b.radius).into(),
+            (ShapeMoving::BoxOriented(a), ShapeStatic::BoxAligned(b)     ) => BoxOrientedBoxy::new(b.origin, a.size, a.direction, b.size).into(),
+            (ShapeMoving::BoxOriented(a), ShapeStatic::BoxAlignedRound(b)) => BoxOrientedBoxyRound::new(b.origin, a.size, a.direction, b.size, b.radius).into(),
+
+            (ShapeMoving::Ball(a),       ShapeStatic::Capsule(b)) => Capsule::new(b.origin, b.direction, b.length, b.radius + a.radius).into(),
+            (ShapeMoving::BoxAligned(_), ShapeStatic::Capsule(_)) => Self::between_convex_hulls(a, b.get_convex_hull(a.origin())),
+            (ShapeMoving::Capsule(a),    ShapeStatic::Ball(b)   ) => Capsule::new(b.origin, a.direction, a.length, a.radius + b.radius).into(),
+
+            (ShapeMoving::Ball(a),       ShapeStatic::PolygonSmall(b)     ) => PolygonSmallRound::new(*b, a.radius).into(),
+            (ShapeMoving::Ball(a),       ShapeStatic::PolygonSmallRound(b)) => PolygonSmallRound::new(b.inner, b.radius + a.radius).into(),
+            (ShapeMoving::BoxAligned(_), ShapeStatic::PolygonSmall(_)     ) => Self::between_convex_hulls(a, b.get_convex_hull(a.origin())),
+            (ShapeMoving::BoxAligned(_), ShapeStatic::PolygonSmallRound(_)) => Self::between_convex_hulls(a, b.get_convex_hull(a.origin())),
+
+            (ShapeMoving::BoxOriented(_), _) => Self::between_convex_hulls(a, b.get_convex_hull(a.origin())),
+            (ShapeMoving::PolygonSmall(_), _) => Self::between_convex_hulls(a, b.get_convex_hull(a.origin())),
+            (ShapeMoving::Capsule(_), _) => Self::between_convex_hulls(a, b.get_convex_hull(a.origin())),
         } 
     }
 
@@ -159,7 +233,36 @@ impl ShapeCombined {
             (ShapeMoving::Ball(a),       ShapeMoving::BoxAligned(b)) => BoxAlignedRound::new(b.origin, b.size, a.radius).into(),
             (ShapeMoving::BoxAligned(a), ShapeMoving::Ball(b)      ) => BoxAlignedRound::new(b.origin, a.size, b.radius).into(),
             (ShapeMoving::BoxAligned(a), ShapeMoving::BoxAligned(b)) => BoxAligned::new(b.origin, a.size + b.size).into(),
+
+            (ShapeMoving::Ball(a),        ShapeMoving::BoxOriented(b)) => BoxOrientedRound::new(b.origin, b.size, b.direction, a.radius).into(),
+            (ShapeMoving::BoxAligned(a),  ShapeMoving::BoxOriented(b)) => BoxOrientedBoxy::new(b.origin, b.size, b.direction, a.size).into(),
+            (ShapeMoving::BoxOriented(a), ShapeMoving::Ball(b)       ) => BoxOrientedRound::new(b.origin, a.size, a.direction, b.radius).into(),
+            (ShapeMoving::BoxOriented(a), ShapeMoving::BoxAligned(b) ) => BoxOrientedBoxy::new(b.origin, a.size, a.direction, b.size).into(),
+
+            (ShapeMoving::Ball(a),        ShapeMoving::Capsule(b)  ) => Capsule::new(b.origin, b.direction, b.length, b.radius + a.radius).into(),
+            (ShapeMoving::Capsule(a),     ShapeMoving::Ball(b)     ) => Capsule::new(b.origin, a.direction, a.length, a.radius + b.radius).into(),
+
+            (ShapeMoving::BoxOriented(_), _) => Self::between_convex_hulls(a, b.get_convex_hull()),
+            (ShapeMoving::PolygonSmall(_), _) => Self::between_convex_hulls(a, b.get_convex_hull()),
+            (ShapeMoving::Capsule(_), _) => Self::between_convex_hulls(a, b.get_convex_hull()),
+            (_, ShapeMoving::PolygonSmall(_)) => Self::between_convex_hulls(a, b.get_convex_hull()),
+            (_, ShapeMoving::Capsule(_)) => Self::between_convex_hulls(a, b.get_convex_hull()),
         }
     }
 
+    fn between_convex_hulls(a: &ShapeMoving, (b_points, b_radius): (ArrayVec<[Vec2; POLYGON_SMALL_CAPACITY]>, f32)) -> Self {
+        let (a_points, a_radius) = a.get_reflected_hull();
+        PolygonMinkowski::new(&a_points, &b_points, a_radius + b_radius).into()
+    }
+
+    fn between_polygon_large(a: &ShapeMoving, b: &PolygonLarge) -> Self {
+        let (a_points, a_radius) = a.get_reflected_hull();
+        EdgeChainMinkowski::new_from_polygon(&a_points, a_radius, b).into()
+    }
+
+    fn between_edge_chain(a: &ShapeMoving, b: &EdgeChain) -> Self {
+        let (a_points, a_radius) = a.get_reflected_hull();
+        EdgeChainMinkowski::new_from_chain(&a_points, a_radius, b).into()
+    }
+
 }
\ No newline at end of file
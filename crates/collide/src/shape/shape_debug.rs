@@ -15,6 +15,10 @@ pub enum ShapeDebugData {
         points:  Box<[Vec2]>,
         normals: Box<[Vec2]>,
         radius:  f32,
+    },
+    Chain{
+        points:  Box<[Vec2]>,
+        normals: Box<[Vec2]>,
     }
 }
 
@@ -32,14 +36,19 @@ impl ShapeDebugData {
         Self::PolygonRound{points, normals, radius}
     }
 
+    pub fn chain(points: Box<[Vec2]>, normals: Box<[Vec2]>) -> Self {
+        Self::Chain{points, normals}
+    }
+
     pub fn iter_segments(&self) -> impl Iterator<Item = ([Vec2; 3], f32)> + '_ {
         let ([points, normals], offset) = match self {
             ShapeDebugData::Circle { .. } => ([[].as_ref(), [].as_ref()], 0.0_f32),
             ShapeDebugData::Polygon { points, normals } => ([points.as_ref(), normals.as_ref()], 0.0_f32),
             ShapeDebugData::PolygonRound { points, normals, radius } => ([points.as_ref(), normals.as_ref()], *radius),
+            ShapeDebugData::Chain { points, normals } => ([points.as_ref(), normals.as_ref()], 0.0_f32),
         };
 
-        (0..points.len()).map(move |i| {
+        (0..normals.len()).map(move |i| {
             let norm = normals[i];
             let from = points[i];
             let to   = points[(i+1) % points.len()];
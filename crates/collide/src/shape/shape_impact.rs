@@ -0,0 +1,213 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use bevy::prelude::Vec2;
+
+use crate::prelude::{RayCaster, ShapeClosest, ShapeCombined, ShapeMoving, ShapeStatic};
+
+#[derive(Debug, Clone, Copy)]
+pub struct ShapeImpact {
+    pub time:   f32,
+    pub point:  Vec2,
+    pub normal: Vec2,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ShapeCast {
+    pub distance: f32,
+    pub origin:   Vec2,
+    pub point:    Vec2,
+    pub normal:   Vec2,
+}
+
+impl ShapeMoving {
+
+    pub fn find_impact_static(&self, velocity: Vec2, other: &ShapeStatic, max_time: f32) -> Option<ShapeImpact> {
+        let combined = ShapeCombined::between_moving_and_static(self, other);
+        let impact   = find_time_of_impact(&combined, self.origin(), velocity, max_time)?;
+        Some(find_impact_contact(
+            self.get_convex_hull(),
+            other.get_convex_hull(self.origin() + velocity*impact.0),
+            velocity*impact.0,
+            Vec2::ZERO,
+            impact
+        ))
+    }
+
+    pub fn find_impact_moving(&self, velocity: Vec2, other: &ShapeMoving, other_velocity: Vec2, max_time: f32) -> Option<ShapeImpact> {
+        let combined = ShapeCombined::between_moving(self, other);
+        let impact   = find_time_of_impact(&combined, self.origin(), velocity - other_velocity, max_time)?;
+        Some(find_impact_contact(
+            self.get_convex_hull(),
+            other.get_convex_hull(),
+            velocity*impact.0,
+            other_velocity*impact.0,
+            impact
+        ))
+    }
+
+}
+
+// NOTE Casting against the combined shape only tells us where our origin stops,
+//      the contact point is found afterwards from the shapes at that position.
+//      Starting inside stops us where we are, pointing the shortest way out.
+
+impl ShapeMoving {
+
+    pub fn cast_static(&self, direction: Vec2, max_dist: f32, other: &ShapeStatic) -> Option<ShapeCast> {
+        let (distance, origin, normal) = find_cast(&ShapeCombined::between_moving_and_static(self, other), self.origin(), direction, max_dist)?;
+        Some(ShapeCast{
+            distance,
+            origin,
+            point: self.find_contact_static(origin - self.origin(), normal, other),
+            normal,
+        })
+    }
+
+    pub fn cast_moving(&self, direction: Vec2, max_dist: f32, other: &ShapeMoving) -> Option<ShapeCast> {
+        let (distance, origin, normal) = find_cast(&ShapeCombined::between_moving(self, other), self.origin(), direction, max_dist)?;
+        Some(ShapeCast{
+            distance,
+            origin,
+            point: self.find_contact_moving(origin - self.origin(), normal, other),
+            normal,
+        })
+    }
+
+    pub fn find_contact_static(&self, offset: Vec2, normal: Vec2, other: &ShapeStatic) -> Vec2 {
+        find_impact_contact(self.get_convex_hull(), other.get_convex_hull(self.origin() + offset), offset, Vec2::ZERO, (0.0, normal)).point
+    }
+
+    pub fn find_contact_moving(&self, offset: Vec2, normal: Vec2, other: &ShapeMoving) -> Vec2 {
+        find_impact_contact(self.get_convex_hull(), other.get_convex_hull(), offset, Vec2::ZERO, (0.0, normal)).point
+    }
+
+}
+
+// NOTE We sweep our origin through the minkowski sum along the relative velocity,
+//      as if the other shape was standing still. If we start overlapping then
+//      the impact is immediate, and the normal is the shortest way out instead.
+
+fn find_time_of_impact(combined: &ShapeCombined, origin: Vec2, velocity: Vec2, max_time: f32) -> Option<(f32, Vec2)> {
+    let speed = velocity.length();
+    let (distance, _, normal) = find_cast(combined, origin, velocity, speed*max_time)?;
+    if speed == 0.0 {
+        Some((0.0, combined.closest_point(origin).normal))
+    } else {
+        Some((distance/speed, normal))
+    }
+}
+
+fn find_cast(combined: &ShapeCombined, origin: Vec2, direction: Vec2, max_dist: f32) -> Option<(f32, Vec2, Vec2)> {
+    let ray = RayCaster::new_segment(origin, direction, 0.0, max_dist);
+    let [enter, _] = ray.test(combined)?;
+    if enter.distance < 0.0 {
+        Some((0.0, origin, combined.closest_point(origin).normal))
+    } else {
+        Some((enter.distance, enter.point, enter.normal))
+    }
+}
+
+// NOTE The contact is the middle of where the two touching features overlap, so
+//      flat faces meeting report the middle of the overlap rather than a corner.
+//      When they don't overlap (ie. rounded corners) it's the middle of the gap.
+
+fn find_impact_contact(
+    (points_a, _): (impl AsRef<[Vec2]>, f32),
+    (points_b, radius_b): (impl AsRef<[Vec2]>, f32),
+    offset_a: Vec2,
+    offset_b: Vec2,
+    (time, normal): (f32, Vec2),
+) -> ShapeImpact {
+    let tangent = normal.perp();
+    let (a_min, a_max, _)       = find_support_feature(points_a.as_ref(), offset_a, -normal, tangent);
+    let (b_min, b_max, b_depth) = find_support_feature(points_b.as_ref(), offset_b,  normal, tangent);
+    let along = 0.5*(a_min.max(b_min) + a_max.min(b_max));
+
+    ShapeImpact{
+        time,
+        point: tangent*along + normal*(b_depth + radius_b),
+        normal,
+    }
+}
+
+fn find_support_feature(points: &[Vec2], offset: Vec2, direction: Vec2, tangent: Vec2) -> (f32, f32, f32) {
+    const EPSILON: f32 = 1.0e-4;
+    let depth = points.iter().map(|&v| (v + offset).dot(direction)).fold(-f32::INFINITY, f32::max);
+    let (min, max) = points.iter()
+        .filter(|&&v| (v + offset).dot(direction) >= depth - EPSILON)
+        .map(|&v| (v + offset).dot(tangent))
+        .fold((f32::INFINITY, -f32::INFINITY), |(min, max), v| (min.min(v), max.max(v)));
+    (min, max, depth)
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::Vec2;
+    use crate::prelude::{Ball, BoxAligned, ShapeMoving, ShapeStatic};
+
+    #[test]
+    fn check_cast() {
+        let ground: ShapeStatic = BoxAligned::new(Vec2::ZERO, Vec2::new(2.0, 0.5)).into();
+
+        let mover: ShapeMoving = BoxAligned::new(Vec2::new(1.5, 3.0), Vec2::splat(0.5)).into();
+        let cast = mover.cast_static(-Vec2::Y, 10.0, &ground).unwrap();
+        assert!((cast.distance - 2.0).abs() < 1.0e-5);
+        assert!((cast.origin - Vec2::new(1.5, 1.0)).length() < 1.0e-5);
+        assert!((cast.point  - Vec2::new(1.5, 0.5)).length() < 1.0e-5);
+
+        // Hanging off the edge, we touch the corner of the ground
+        let mover: ShapeMoving = Ball::new(Vec2::new(2.3, 3.0), 0.5).into();
+        let cast = mover.cast_static(-Vec2::Y, 10.0, &ground).unwrap();
+        assert!((cast.point - Vec2::new(2.0, 0.5)).length() < 1.0e-5);
+        assert!(((cast.origin - cast.point).length() - 0.5).abs() < 1.0e-5);
+
+        assert!(mover.cast_static(-Vec2::Y, 1.0, &ground).is_none());
+
+        // Starting inside, we don't move and get pushed out of the top
+        let mover: ShapeMoving = BoxAligned::new(Vec2::new(0.5, 0.8), Vec2::splat(0.5)).into();
+        let cast = mover.cast_static(Vec2::X, 10.0, &ground).unwrap();
+        assert_eq!(cast.distance, 0.0);
+        assert_eq!(cast.origin, Vec2::new(0.5, 0.8));
+        assert!((cast.normal - Vec2::Y).length() < 1.0e-5);
+        assert!((cast.point  - Vec2::new(0.5, 0.5)).length() < 1.0e-5);
+
+        let other: ShapeMoving = Ball::new(Vec2::new(1.0, 0.0), 0.5).into();
+        let mover: ShapeMoving = Ball::new(Vec2::new(0.6, 0.0), 0.5).into();
+        let cast = mover.cast_moving(Vec2::X, 10.0, &other).unwrap();
+        assert_eq!(cast.distance, 0.0);
+        assert_eq!(cast.origin, Vec2::new(0.6, 0.0));
+        assert!((cast.normal + Vec2::X).length() < 1.0e-5);
+    }
+
+    #[test]
+    fn check_impact() {
+        // Head on, both moving towards each other
+        let a: ShapeMoving = Ball::new(Vec2::new(-2.0, 0.0), 0.5).into();
+        let b: ShapeMoving = Ball::new(Vec2::new( 2.0, 0.0), 0.5).into();
+        let impact = a.find_impact_moving(Vec2::X, &b, -Vec2::X, 10.0).unwrap();
+        assert!((impact.time - 1.5).abs() < 1.0e-5);
+        assert!((impact.point  - Vec2::ZERO).length() < 1.0e-5);
+        assert!((impact.normal + Vec2::X).length() < 1.0e-5);
+
+        // Moving together never touches
+        assert!(a.find_impact_moving(Vec2::X, &b, Vec2::X, 10.0).is_none());
+
+        // Too slow to touch this tick
+        assert!(a.find_impact_moving(Vec2::X, &b, -Vec2::X, 1.0).is_none());
+
+        // Crate landing off center on a wider crate, contact is the middle of the overlap
+        let a: ShapeMoving = BoxAligned::new(Vec2::new(1.0, 3.0), Vec2::splat(0.5)).into();
+        let b: ShapeMoving = BoxAligned::new(Vec2::ZERO, Vec2::new(1.0, 0.5)).into();
+        let impact = a.find_impact_moving(-Vec2::Y, &b, Vec2::Y, 10.0).unwrap();
+        assert!((impact.time - 1.0).abs() < 1.0e-5);
+        assert!((impact.point  - Vec2::new(0.75, 1.5)).length() < 1.0e-5);
+        assert!((impact.normal - Vec2::Y).length() < 1.0e-5);
+
+        // Already overlapping
+        let ground: ShapeStatic = BoxAligned::new(Vec2::ZERO, Vec2::new(1.0, 0.5)).into();
+        let a: ShapeMoving = BoxAligned::new(Vec2::new(0.0, 0.9), Vec2::splat(0.5)).into();
+        let impact = a.find_impact_static(Vec2::X, &ground, 1.0).unwrap();
+        assert_eq!(impact.time, 0.0);
+        assert!((impact.normal - Vec2::Y).length() < 1.0e-5);
+    }
+}
@@ -3,14 +3,18 @@
 use bevy::math::Vec2;
 use macro_attr_2018::macro_attr;
 use enum_derive_2018::EnumFromInner;
+use tinyvec::ArrayVec;
 
 use crate::prelude::*;
 
 macro_attr! {
-    #[derive(EnumFromInner!)]
+    #[derive(EnumFromInner!, Debug, Copy, Clone)]
     pub enum ShapeMoving {
         Ball(Ball),
         BoxAligned(BoxAligned),
+        BoxOriented(BoxOriented),
+        PolygonSmall(PolygonSmall),
+        Capsule(Capsule),
     }
 }
 
@@ -18,32 +22,75 @@ impl ShapeMoving {
 
     pub fn origin(&self) -> Vec2 {
         match self {
-            ShapeMoving::Ball(s)       => s.origin,
-            ShapeMoving::BoxAligned(s) => s.origin,
+            ShapeMoving::Ball(s)         => s.origin,
+            ShapeMoving::BoxAligned(s)   => s.origin,
+            ShapeMoving::BoxOriented(s)  => s.origin,
+            ShapeMoving::PolygonSmall(s) => s.origin(),
+            ShapeMoving::Capsule(s)      => s.origin,
         }
     }
 
+    pub(crate) fn get_convex_hull(&self) -> (ArrayVec<[Vec2; POLYGON_SMALL_CAPACITY]>, f32) {
+        match self {
+            ShapeMoving::Ball(s)         => (ArrayVec::from_iter([s.origin]), s.radius),
+            ShapeMoving::BoxAligned(s)   => (get_polygon_data_for_rect(s.size, Vec2::X).0.map(|v| s.origin + v).into_iter().collect(), 0.0),
+            ShapeMoving::BoxOriented(s)  => (get_polygon_data_for_rect(s.size, s.direction).0.map(|v| s.origin + v).into_iter().collect(), 0.0),
+            ShapeMoving::PolygonSmall(s) => (s.points, 0.0),
+            ShapeMoving::Capsule(s)      => (s.get_points().into_iter().collect(), s.radius),
+        }
+    }
+
+    // NOTE Reflected through our origin, so that our origin sits inside any
+    //      sum taken against it whenever we overlap the other shape.
+    pub(crate) fn get_reflected_hull(&self) -> (ArrayVec<[Vec2; POLYGON_SMALL_CAPACITY]>, f32) {
+        let (points, radius) = self.get_convex_hull();
+        let origin = self.origin();
+        (points.iter().map(|&v| origin - v).collect(), radius)
+    }
+
+}
+
+// NOTE Every moving shape can also be hit by others, so it's a static shape too
+impl From<ShapeMoving> for ShapeStatic {
+    fn from(value: ShapeMoving) -> Self {
+        match value {
+            ShapeMoving::Ball(s)         => s.into(),
+            ShapeMoving::BoxAligned(s)   => s.into(),
+            ShapeMoving::BoxOriented(s)  => s.into(),
+            ShapeMoving::PolygonSmall(s) => s.into(),
+            ShapeMoving::Capsule(s)      => s.into(),
+        }
+    }
 }
 
 impl ShapeCommon for ShapeMoving {
     fn bounding_box(&self) -> BoxAligned {
         match self {
-            ShapeMoving::Ball(s)       => s.bounding_box(),
-            ShapeMoving::BoxAligned(s) => s.bounding_box(),
+            ShapeMoving::Ball(s)         => s.bounding_box(),
+            ShapeMoving::BoxAligned(s)   => s.bounding_box(),
+            ShapeMoving::BoxOriented(s)  => s.bounding_box(),
+            ShapeMoving::PolygonSmall(s) => s.bounding_box(),
+            ShapeMoving::Capsule(s)      => s.bounding_box(),
         }
     }
 
     fn origin(&self) -> Vec2 {
         match self {
-            ShapeMoving::Ball(s)       => s.origin(),
-            ShapeMoving::BoxAligned(s) => s.origin(),
+            ShapeMoving::Ball(s)         => s.origin(),
+            ShapeMoving::BoxAligned(s)   => s.origin(),
+            ShapeMoving::BoxOriented(s)  => s.origin(),
+            ShapeMoving::PolygonSmall(s) => s.origin(),
+            ShapeMoving::Capsule(s)      => s.origin(),
         }
     }
 
     fn set_origin(&mut self, origin: Vec2) {
         match self {
-            ShapeMoving::Ball(s)       => s.set_origin(origin),
-            ShapeMoving::BoxAligned(s) => s.set_origin(origin),
+            ShapeMoving::Ball(s)         => s.set_origin(origin),
+            ShapeMoving::BoxAligned(s)   => s.set_origin(origin),
+            ShapeMoving::BoxOriented(s)  => s.set_origin(origin),
+            ShapeMoving::PolygonSmall(s) => s.set_origin(origin),
+            ShapeMoving::Capsule(s)      => s.set_origin(origin),
         }
     }
 }
@@ -53,6 +100,9 @@ impl ShapeDebug for ShapeMoving {
         match self {
             ShapeMoving::Ball(s) => s.get_debug_shape_data(),
             ShapeMoving::BoxAligned(s) => s.get_debug_shape_data(),
+            ShapeMoving::BoxOriented(s) => s.get_debug_shape_data(),
+            ShapeMoving::PolygonSmall(s) => s.get_debug_shape_data(),
+            ShapeMoving::Capsule(s) => s.get_debug_shape_data(),
         }
     }
 }
@@ -62,6 +112,9 @@ impl RayTarget for ShapeMoving {
         match self {
             ShapeMoving::Ball(s) => s.raycast(ray),
             ShapeMoving::BoxAligned(s) => s.raycast(ray),
+            ShapeMoving::BoxOriented(s) => s.raycast(ray),
+            ShapeMoving::PolygonSmall(s) => s.raycast(ray),
+            ShapeMoving::Capsule(s) => s.raycast(ray),
         }
     }
 
@@ -69,6 +122,9 @@ impl RayTarget for ShapeMoving {
         match self {
             ShapeMoving::Ball(s) => s.raycast_enter(ray),
             ShapeMoving::BoxAligned(s) => s.raycast_enter(ray),
+            ShapeMoving::BoxOriented(s) => s.raycast_enter(ray),
+            ShapeMoving::PolygonSmall(s) => s.raycast_enter(ray),
+            ShapeMoving::Capsule(s) => s.raycast_enter(ray),
         }
     }
 
@@ -76,6 +132,21 @@ impl RayTarget for ShapeMoving {
         match self {
             ShapeMoving::Ball(s) => s.raycast_exit(ray),
             ShapeMoving::BoxAligned(s) => s.raycast_exit(ray),
+            ShapeMoving::BoxOriented(s) => s.raycast_exit(ray),
+            ShapeMoving::PolygonSmall(s) => s.raycast_exit(ray),
+            ShapeMoving::Capsule(s) => s.raycast_exit(ray),
+        }
+    }
+}
+
+impl ShapeClosest for ShapeMoving {
+    fn closest_point(&self, point: Vec2) -> ClosestPoint {
+        match self {
+            ShapeMoving::Ball(s) => s.closest_point(point),
+            ShapeMoving::BoxAligned(s) => s.closest_point(point),
+            ShapeMoving::BoxOriented(s) => s.closest_point(point),
+            ShapeMoving::PolygonSmall(s) => s.closest_point(point),
+            ShapeMoving::Capsule(s) => s.closest_point(point),
         }
     }
 }
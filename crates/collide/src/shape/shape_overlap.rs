@@ -0,0 +1,167 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use bevy::prelude::Vec2;
+
+use crate::prelude::{ShapeCombined, ShapeMoving, ShapeStatic, ShapeClosest};
+
+#[derive(Debug, Clone, Copy)]
+pub struct ShapeOverlap {
+    pub point:  Vec2,
+    pub normal: Vec2,
+    pub depth:  f32,
+}
+
+impl ShapeOverlap {
+    pub fn get_depenetration(&self) -> Vec2 {
+        self.normal*self.depth
+    }
+}
+
+impl ShapeMoving {
+
+    pub fn overlaps_static(&self, other: &ShapeStatic) -> bool {
+        find_overlap(&ShapeCombined::between_moving_and_static(self, other), self.origin()).is_some()
+    }
+
+    pub fn overlaps_moving(&self, other: &ShapeMoving) -> bool {
+        find_overlap(&ShapeCombined::between_moving(self, other), self.origin()).is_some()
+    }
+
+    pub fn overlap_static(&self, other: &ShapeStatic) -> Option<ShapeOverlap> {
+        let (normal, depth) = find_overlap(&ShapeCombined::between_moving_and_static(self, other), self.origin())?;
+        Some(ShapeOverlap{
+            point: self.find_contact_static(normal*depth, normal, other),
+            normal,
+            depth,
+        })
+    }
+
+    pub fn overlap_moving(&self, other: &ShapeMoving) -> Option<ShapeOverlap> {
+        let (normal, depth) = find_overlap(&ShapeCombined::between_moving(self, other), self.origin())?;
+        Some(ShapeOverlap{
+            point: self.find_contact_moving(normal*depth, normal, other),
+            normal,
+            depth,
+        })
+    }
+
+    pub fn find_depenetration_static(&self, other: &ShapeStatic) -> Option<Vec2> {
+        self.overlap_static(other).map(|v| v.get_depenetration())
+    }
+
+    pub fn find_depenetration_moving(&self, other: &ShapeMoving) -> Option<Vec2> {
+        self.overlap_moving(other).map(|v| v.get_depenetration())
+    }
+
+}
+
+// NOTE The combined shape is the minkowski sum centered on the other shape,
+//      so the two shapes overlap when our origin sits strictly inside it and
+//      the closest point on its surface is the shortest way back out.
+//      Touching doesn't count, otherwise resting contacts would overlap.
+//      The point is where the two shapes touch once we've been pushed out.
+
+fn find_overlap(combined: &ShapeCombined, origin: Vec2) -> Option<(Vec2, f32)> {
+    let closest = combined.closest_point(origin);
+    (closest.distance < 0.0).then_some((closest.normal, -closest.distance))
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::Vec2;
+    use crate::prelude::{Ball, BoxAligned, BoxAlignedRound, Capsule, BoxOriented, BoxOrientedRound, Ramp, RampRound, ShapeMoving, ShapeStatic};
+
+    // NOTE Paired with the height of each shape's lowest point, which is always at x = 0
+    fn get_static_shapes() -> [(ShapeStatic, f32); 7] {
+        use std::f32::consts::FRAC_1_SQRT_2;
+        let turned = Vec2::new(1.0, 1.0).normalize();
+        let ramp   = Ramp::new_from_size_centered(Vec2::ZERO, Vec2::ONE, Vec2::ONE);
+        [
+            (Ball::new(Vec2::ZERO, 0.5).into(),                                         -0.5),
+            (BoxAligned::new(Vec2::ZERO, Vec2::splat(0.5)).into(),                      -0.5),
+            (BoxAlignedRound::new(Vec2::ZERO, Vec2::splat(0.25), 0.25).into(),          -0.5),
+            (BoxOriented::new(Vec2::ZERO, Vec2::splat(0.5), turned).into(),             -FRAC_1_SQRT_2),
+            (BoxOrientedRound::new(Vec2::ZERO, Vec2::splat(0.25), turned, 0.25).into(), -0.25 - 0.5*FRAC_1_SQRT_2),
+            (ramp.into(),                                                               -0.5),
+            (RampRound::new(ramp.origin, ramp.direction, ramp.length, 0.25).into(),     -0.75),
+        ]
+    }
+
+    fn get_moving_shapes(origin: Vec2) -> [ShapeMoving; 2] {
+        [
+            Ball::new(origin, 0.25).into(),
+            BoxAligned::new(origin, Vec2::splat(0.25)).into(),
+        ]
+    }
+
+    #[test]
+    fn check_overlap_pairs() {
+        for (other, bottom) in get_static_shapes() {
+            // Deep inside
+            for shape in get_moving_shapes(Vec2::new(-0.25, -0.25)) {
+                let overlap = shape.overlap_static(&other).unwrap();
+                assert!(overlap.depth > 0.0 && (overlap.normal.length() - 1.0).abs() < 1.0e-5);
+                assert!(shape.overlaps_static(&other));
+            }
+
+            // Just underneath, so the shortest way out is straight back down
+            for shape in get_moving_shapes(Vec2::new(0.0, bottom - 0.2)) {
+                let overlap = shape.overlap_static(&other).unwrap();
+                assert!((overlap.depth - 0.05).abs() < 1.0e-4, "{:?}", overlap);
+                assert!((overlap.normal + Vec2::Y).length() < 1.0e-4, "{:?}", overlap);
+                assert!((overlap.point - Vec2::new(0.0, bottom)).length() < 1.0e-4, "{:?}", overlap);
+            }
+
+            // Only just clear, and well clear
+            for shape in get_moving_shapes(Vec2::new(0.0, bottom - 0.26)).into_iter().chain(get_moving_shapes(Vec2::new(0.0, bottom - 1.0))) {
+                assert!(shape.overlap_static(&other).is_none());
+                assert!(!shape.overlaps_static(&other));
+            }
+        }
+    }
+
+    #[test]
+    fn check_overlap_moving_pairs() {
+        for other in get_moving_shapes(Vec2::ZERO) {
+            for shape in get_moving_shapes(Vec2::new(0.0, 0.45)) {
+                let overlap = shape.overlap_moving(&other).unwrap();
+                assert!((overlap.depth - 0.05).abs() < 1.0e-4, "{:?}", overlap);
+                assert!((overlap.normal - Vec2::Y).length() < 1.0e-4, "{:?}", overlap);
+                assert!((overlap.point - Vec2::new(0.0, 0.25)).length() < 1.0e-4, "{:?}", overlap);
+            }
+
+            for shape in get_moving_shapes(Vec2::new(0.0, 0.5)) {
+                assert!(!shape.overlaps_moving(&other));
+            }
+        }
+    }
+
+    #[test]
+    fn check_depenetration() {
+        let mover: ShapeMoving = BoxAligned::new(Vec2::new(0.0, 0.9), Vec2::splat(0.5)).into();
+        let ground: ShapeStatic = BoxAligned::new(Vec2::ZERO, Vec2::splat(0.5)).into();
+        let push = mover.find_depenetration_static(&ground).unwrap();
+        assert!((push - Vec2::new(0.0, 0.1)).length() < 1.0e-5);
+
+        // Hanging off the edge, the contact is only where they still touch
+        let mover: ShapeMoving = BoxAligned::new(Vec2::new(0.75, 0.9), Vec2::splat(0.5)).into();
+        let overlap = mover.overlap_static(&ground).unwrap();
+        assert!((overlap.point - Vec2::new(0.375, 0.5)).length() < 1.0e-5);
+
+        let mover: ShapeMoving = Ball::new(Vec2::new(0.75, 0.0), 0.5).into();
+        let push = mover.find_depenetration_static(&ground).unwrap();
+        assert!((push - Vec2::new(0.25, 0.0)).length() < 1.0e-5);
+
+        let mover: ShapeMoving = BoxAligned::new(Vec2::new(0.0, 1.0), Vec2::splat(0.5)).into();
+        assert!(!mover.overlaps_static(&ground));
+
+        let mover: ShapeMoving = Capsule::new_vertical(Vec2::new(0.5, 1.4), 2.0, 0.5).into();
+        let push = mover.find_depenetration_static(&ground).unwrap();
+        assert!((push - Vec2::new(0.0, 0.1)).length() < 1.0e-5);
+
+        let slope: ShapeStatic = Capsule::new(Vec2::ZERO, Vec2::X, 2.0, 0.5).into();
+        let mover: ShapeMoving = Ball::new(Vec2::new(1.5, 0.0), 0.5).into();
+        let push = mover.find_depenetration_static(&slope).unwrap();
+        assert!((push - Vec2::new(0.5, 0.0)).length() < 1.0e-5);
+    }
+}
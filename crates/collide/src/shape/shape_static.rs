@@ -3,11 +3,12 @@
 use bevy::math::Vec2;
 use macro_attr_2018::macro_attr;
 use enum_derive_2018::EnumFromInner;
+use tinyvec::ArrayVec;
 
 use crate::prelude::*;
 
 macro_attr! {
-    #[derive(EnumFromInner!, Debug, Copy, Clone)]
+    #[derive(EnumFromInner!, Debug, Clone)]
     pub enum ShapeStatic {
         Ball(Ball),
         BoxAligned(BoxAligned),
@@ -16,9 +17,41 @@ macro_attr! {
         BoxOrientedRound(BoxOrientedRound),
         Ramp(Ramp),
         RampRound(RampRound),
+        Capsule(Capsule),
+        PolygonSmall(PolygonSmall),
+        PolygonSmallRound(PolygonSmallRound),
+        PolygonLarge(PolygonLarge),
+        EdgeChain(EdgeChain),
     }
 }
 
+impl ShapeStatic {
+
+    // NOTE Shapes that aren't convex give the edge that's nearest instead
+    pub(crate) fn get_convex_hull(&self, near: Vec2) -> (ArrayVec<[Vec2; POLYGON_SMALL_CAPACITY]>, f32) {
+        match self {
+            ShapeStatic::Ball(s)              => (ArrayVec::from_iter([s.origin]), s.radius),
+            ShapeStatic::BoxAligned(s)        => (get_polygon_data_for_rect(s.size, Vec2::X).0.map(|v| s.origin + v).into_iter().collect(), 0.0),
+            ShapeStatic::BoxAlignedRound(s)   => (get_polygon_data_for_rect(s.size, Vec2::X).0.map(|v| s.origin + v).into_iter().collect(), s.radius),
+            ShapeStatic::BoxOriented(s)       => (get_polygon_data_for_rect(s.size, s.direction).0.map(|v| s.origin + v).into_iter().collect(), 0.0),
+            ShapeStatic::BoxOrientedRound(s)  => (get_polygon_data_for_rect(s.size, s.direction).0.map(|v| s.origin + v).into_iter().collect(), s.radius),
+            ShapeStatic::Ramp(s)              => (get_polygon_data_for_ramp(s.direction, s.length).0.map(|v| s.origin + v).into_iter().collect(), 0.0),
+            ShapeStatic::RampRound(s)         => (get_polygon_data_for_ramp(s.direction, s.length).0.map(|v| s.origin + v).into_iter().collect(), s.radius),
+            ShapeStatic::Capsule(s)           => (s.get_points().into_iter().collect(), s.radius),
+            ShapeStatic::PolygonSmall(s)      => (s.points, 0.0),
+            ShapeStatic::PolygonSmallRound(s) => (s.inner.points, s.radius),
+            ShapeStatic::PolygonLarge(s)      => (get_nearest_edge(&s.points, &s.normals, &s.lengths, near).into_iter().collect(), 0.0),
+            ShapeStatic::EdgeChain(s)         => (get_nearest_edge(&s.points, &s.normals, &s.lengths, near).into_iter().collect(), 0.0),
+        }
+    }
+
+}
+
+fn get_nearest_edge(points: &[Vec2], normals: &[Vec2], lengths: &[f32], near: Vec2) -> [Vec2; 2] {
+    let (idx, _, _) = find_closest_on_edges(points, normals, lengths, near);
+    [points[idx], points[(idx+1)%points.len()]]
+}
+
 impl ShapeCommon for ShapeStatic {
     fn bounding_box(&self) -> BoxAligned {
         match self {
@@ -29,6 +62,11 @@ impl ShapeCommon for ShapeStatic {
             ShapeStatic::BoxOrientedRound(s) => s.bounding_box(),
             ShapeStatic::Ramp(s) => s.bounding_box(),
             ShapeStatic::RampRound(s) => s.bounding_box(),
+            ShapeStatic::Capsule(s) => s.bounding_box(),
+            ShapeStatic::PolygonSmall(s) => s.bounding_box(),
+            ShapeStatic::PolygonSmallRound(s) => s.bounding_box(),
+            ShapeStatic::PolygonLarge(s) => s.bounding_box(),
+            ShapeStatic::EdgeChain(s) => s.bounding_box(),
         }
     }
 
@@ -41,6 +79,11 @@ impl ShapeCommon for ShapeStatic {
             ShapeStatic::BoxOrientedRound(s) => s.origin(),
             ShapeStatic::Ramp(s) => s.origin(),
             ShapeStatic::RampRound(s) => s.origin(),
+            ShapeStatic::Capsule(s) => s.origin(),
+            ShapeStatic::PolygonSmall(s) => s.origin(),
+            ShapeStatic::PolygonSmallRound(s) => s.origin(),
+            ShapeStatic::PolygonLarge(s) => s.origin(),
+            ShapeStatic::EdgeChain(s) => s.origin(),
         }
     }
 
@@ -53,6 +96,11 @@ impl ShapeCommon for ShapeStatic {
             ShapeStatic::BoxOrientedRound(s) => s.set_origin(origin),
             ShapeStatic::Ramp(s) => s.set_origin(origin),
             ShapeStatic::RampRound(s) => s.set_origin(origin),
+            ShapeStatic::Capsule(s) => s.set_origin(origin),
+            ShapeStatic::PolygonSmall(s) => s.set_origin(origin),
+            ShapeStatic::PolygonSmallRound(s) => s.set_origin(origin),
+            ShapeStatic::PolygonLarge(s) => s.set_origin(origin),
+            ShapeStatic::EdgeChain(s) => s.set_origin(origin),
         }
     }
 }
@@ -67,6 +115,11 @@ impl ShapeDebug for ShapeStatic {
             ShapeStatic::BoxOrientedRound(s) => s.get_debug_shape_data(),
             ShapeStatic::Ramp(s) => s.get_debug_shape_data(),
             ShapeStatic::RampRound(s) => s.get_debug_shape_data(),
+            ShapeStatic::Capsule(s) => s.get_debug_shape_data(),
+            ShapeStatic::PolygonSmall(s) => s.get_debug_shape_data(),
+            ShapeStatic::PolygonSmallRound(s) => s.get_debug_shape_data(),
+            ShapeStatic::PolygonLarge(s) => s.get_debug_shape_data(),
+            ShapeStatic::EdgeChain(s) => s.get_debug_shape_data(),
         }
     }
 }
@@ -81,6 +134,11 @@ impl RayTarget for ShapeStatic {
             ShapeStatic::BoxOrientedRound(s) => s.raycast(ray),
             ShapeStatic::Ramp(s) => s.raycast(ray),
             ShapeStatic::RampRound(s) => s.raycast(ray),
+            ShapeStatic::Capsule(s) => s.raycast(ray),
+            ShapeStatic::PolygonSmall(s) => s.raycast(ray),
+            ShapeStatic::PolygonSmallRound(s) => s.raycast(ray),
+            ShapeStatic::PolygonLarge(s) => s.raycast(ray),
+            ShapeStatic::EdgeChain(s) => s.raycast(ray),
         }
     }
 
@@ -93,6 +151,11 @@ impl RayTarget for ShapeStatic {
             ShapeStatic::BoxOrientedRound(s) => s.raycast_enter(ray),
             ShapeStatic::Ramp(s) => s.raycast_enter(ray),
             ShapeStatic::RampRound(s) => s.raycast_enter(ray),
+            ShapeStatic::Capsule(s) => s.raycast_enter(ray),
+            ShapeStatic::PolygonSmall(s) => s.raycast_enter(ray),
+            ShapeStatic::PolygonSmallRound(s) => s.raycast_enter(ray),
+            ShapeStatic::PolygonLarge(s) => s.raycast_enter(ray),
+            ShapeStatic::EdgeChain(s) => s.raycast_enter(ray),
         }
     }
 
@@ -105,6 +168,30 @@ impl RayTarget for ShapeStatic {
             ShapeStatic::BoxOrientedRound(s) => s.raycast_exit(ray),
             ShapeStatic::Ramp(s) => s.raycast_exit(ray),
             ShapeStatic::RampRound(s) => s.raycast_exit(ray),
+            ShapeStatic::Capsule(s) => s.raycast_exit(ray),
+            ShapeStatic::PolygonSmall(s) => s.raycast_exit(ray),
+            ShapeStatic::PolygonSmallRound(s) => s.raycast_exit(ray),
+            ShapeStatic::PolygonLarge(s) => s.raycast_exit(ray),
+            ShapeStatic::EdgeChain(s) => s.raycast_exit(ray),
+        }
+    }
+}
+
+impl ShapeClosest for ShapeStatic {
+    fn closest_point(&self, point: Vec2) -> ClosestPoint {
+        match self {
+            ShapeStatic::Ball(s) => s.closest_point(point),
+            ShapeStatic::BoxAligned(s) => s.closest_point(point),
+            ShapeStatic::BoxAlignedRound(s) => s.closest_point(point),
+            ShapeStatic::BoxOriented(s) => s.closest_point(point),
+            ShapeStatic::BoxOrientedRound(s) => s.closest_point(point),
+            ShapeStatic::Ramp(s) => s.closest_point(point),
+            ShapeStatic::RampRound(s) => s.closest_point(point),
+            ShapeStatic::Capsule(s) => s.closest_point(point),
+            ShapeStatic::PolygonSmall(s) => s.closest_point(point),
+            ShapeStatic::PolygonSmallRound(s) => s.closest_point(point),
+            ShapeStatic::PolygonLarge(s) => s.closest_point(point),
+            ShapeStatic::EdgeChain(s) => s.closest_point(point),
         }
     }
 }
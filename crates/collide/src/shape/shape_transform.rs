@@ -0,0 +1,263 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use bevy::math::{Affine2, Mat2, Vec2};
+
+use crate::prelude::*;
+
+// NOTE The transform is applied to the shape where it is, so the origin moves
+//      too. Anything round can only be scaled uniformly, since we've no ellipses.
+//      Boxes that get sheared become polygons, as do ramps that get rotated.
+//      Mirroring flips the winding of polygons, so their points get reversed.
+
+impl ShapeStatic {
+
+    pub fn transformed(&self, transform: &Affine2) -> Result<ShapeStatic, &'static str> {
+        let frame = Frame::new(transform)?;
+        Ok(match self {
+            ShapeStatic::Ball(s)              => frame.transform_ball(s)?.into(),
+            ShapeStatic::BoxAligned(s)        => frame.transform_box(s.origin, s.size, Vec2::X).into(),
+            ShapeStatic::BoxAlignedRound(s)   => frame.transform_box_round(s.origin, s.size, Vec2::X, s.radius)?,
+            ShapeStatic::BoxOriented(s)       => frame.transform_box(s.origin, s.size, s.direction).into(),
+            ShapeStatic::BoxOrientedRound(s)  => frame.transform_box_round(s.origin, s.size, s.direction, s.radius)?,
+            ShapeStatic::Ramp(s)              => frame.transform_ramp(s),
+            ShapeStatic::RampRound(s)         => frame.transform_ramp_round(s)?,
+            ShapeStatic::Capsule(s)           => frame.transform_capsule(s)?.into(),
+            ShapeStatic::PolygonSmall(s)      => frame.transform_polygon(s.points()).into(),
+            ShapeStatic::PolygonSmallRound(s) => PolygonSmallRound::new(
+                frame.transform_polygon(s.inner().points()),
+                frame.scale_radius(s.radius())?
+            ).into(),
+            ShapeStatic::PolygonLarge(s)      => PolygonLarge::new_from_points(frame.transform_points(s.points())).into(),
+            ShapeStatic::EdgeChain(s)         => frame.transform_chain(s).into(),
+        })
+    }
+
+}
+
+impl ShapeMoving {
+
+    pub fn transformed(&self, transform: &Affine2) -> Result<ShapeMoving, &'static str> {
+        let frame = Frame::new(transform)?;
+        Ok(match self {
+            ShapeMoving::Ball(s)         => frame.transform_ball(s)?.into(),
+            ShapeMoving::BoxAligned(s)   => frame.transform_box(s.origin, s.size, Vec2::X),
+            ShapeMoving::BoxOriented(s)  => frame.transform_box(s.origin, s.size, s.direction),
+            ShapeMoving::PolygonSmall(s) => frame.transform_polygon(s.points()).into(),
+            ShapeMoving::Capsule(s)      => frame.transform_capsule(s)?.into(),
+        })
+    }
+
+}
+
+struct Frame {
+    transform: Affine2,
+    // NOTE Only set when the transform keeps circles as circles
+    scale:     Option<f32>,
+    mirrored:  bool,
+}
+
+impl Frame {
+
+    const EPSILON: f32 = 1.0e-4;
+
+    fn new(transform: &Affine2) -> Result<Self, &'static str> {
+        if !transform.is_finite() {
+            return Err("Transform is not finite");
+        }
+
+        let matrix = transform.matrix2;
+        let det    = matrix.determinant();
+        let [x_len, y_len] = [matrix.x_axis.length(), matrix.y_axis.length()];
+        if det.abs() <= Self::EPSILON*x_len*y_len {
+            return Err("Transform is not invertible");
+        }
+
+        let is_uniform = (x_len - y_len).abs() <= Self::EPSILON*x_len.max(y_len)
+            && matrix.x_axis.dot(matrix.y_axis).abs() <= Self::EPSILON*x_len*y_len;
+
+        Ok(Self{
+            transform: *transform,
+            scale:     is_uniform.then_some(x_len),
+            mirrored:  det < 0.0,
+        })
+    }
+
+    fn scale_radius(&self, radius: f32) -> Result<f32, &'static str> {
+        self.scale.map(|v| v*radius).ok_or("Round shapes can only be scaled uniformly")
+    }
+
+    fn transform_ball(&self, ball: &Ball) -> Result<Ball, &'static str> {
+        Ok(Ball::new(self.transform.transform_point2(ball.origin), self.scale_radius(ball.radius)?))
+    }
+
+    fn transform_capsule(&self, capsule: &Capsule) -> Result<Capsule, &'static str> {
+        let direction = self.transform.transform_vector2(capsule.direction).normalize();
+        Ok(Capsule::new(
+            self.transform.transform_point2(capsule.origin),
+            direction,
+            self.scale_radius(capsule.length)?,
+            self.scale_radius(capsule.radius)?
+        ))
+    }
+
+    // NOTE The box's axes only stay square if they're scaled along them,
+    //      otherwise it's sheared into a parallelogram.
+    fn transform_box(&self, origin: Vec2, size: Vec2, direction: Vec2) -> ShapeMoving {
+        let axis_x = self.transform.transform_vector2(direction);
+        let axis_y = self.transform.transform_vector2(direction.perp());
+        let (len_x, len_y) = (axis_x.length(), axis_y.length());
+
+        if axis_x.dot(axis_y).abs() > Self::EPSILON*len_x*len_y {
+            let corners = [
+                Vec2::new( size.x,  size.y),
+                Vec2::new(-size.x,  size.y),
+                Vec2::new(-size.x, -size.y),
+                Vec2::new( size.x, -size.y),
+            ];
+            return self.transform_polygon(&corners.map(|v| origin + Mat2::from_cols(direction, direction.perp())*v)).into();
+        }
+
+        let origin = self.transform.transform_point2(origin);
+
+        // Boxes are symmetric, so mirroring doesn't matter here
+        let size      = size*Vec2::new(len_x, len_y);
+        let direction = axis_x/len_x;
+        if direction.y.abs() <= Self::EPSILON {
+            BoxAligned::new(origin, size).into()
+        } else if direction.x.abs() <= Self::EPSILON {
+            BoxAligned::new(origin, Vec2::new(size.y, size.x)).into()
+        } else {
+            BoxOriented::new(origin, size, direction).into()
+        }
+    }
+
+    fn transform_box_round(&self, origin: Vec2, size: Vec2, direction: Vec2, radius: f32) -> Result<ShapeStatic, &'static str> {
+        let radius = self.scale_radius(radius)?;
+        Ok(match self.transform_box(origin, size, direction) {
+            ShapeMoving::BoxAligned(s)  => BoxAlignedRound::new(s.origin, s.size, radius).into(),
+            ShapeMoving::BoxOriented(s) => BoxOrientedRound::new(s.origin, s.size, s.direction, radius).into(),
+            _ => unreachable!("Uniform scales can't shear boxes"),
+        })
+    }
+
+    // NOTE Ramps are always axis aligned, so they only stay ramps if the
+    //      transform only scales (or flips) along each axis.
+    fn transform_ramp(&self, ramp: &Ramp) -> ShapeStatic {
+        let origin = self.transform.transform_point2(ramp.origin);
+        let matrix = self.transform.matrix2;
+        if matrix.x_axis.y.abs() > Self::EPSILON*matrix.x_axis.x.abs() || matrix.y_axis.x.abs() > Self::EPSILON*matrix.y_axis.y.abs() {
+            let (points, _, _) = get_polygon_data_for_ramp(ramp.direction, ramp.length);
+            return self.transform_polygon(&points.map(|v| ramp.origin + v)).into();
+        }
+
+        // Same as Ramp::new_from_size, the direction's Y is flipped
+        let size   = Vec2::new(ramp.direction.x, -ramp.direction.y)*ramp.length*Vec2::new(matrix.x_axis.x, matrix.y_axis.y);
+        let length = size.length();
+        Ramp::new(origin, Vec2::new(size.x, -size.y)/length, length).into()
+    }
+
+    fn transform_ramp_round(&self, ramp: &RampRound) -> Result<ShapeStatic, &'static str> {
+        let radius = self.scale_radius(ramp.radius)?;
+        Ok(match self.transform_ramp(&Ramp::new(ramp.origin, ramp.direction, ramp.length)) {
+            ShapeStatic::Ramp(s)         => RampRound::new(s.origin, s.direction, s.length, radius).into(),
+            ShapeStatic::PolygonSmall(s) => PolygonSmallRound::new(s, radius).into(),
+            _ => unreachable!("Ramps only become ramps or polygons"),
+        })
+    }
+
+    fn transform_polygon(&self, points: &[Vec2]) -> PolygonSmall {
+        PolygonSmall::new_from_points(self.transform_points(points))
+    }
+
+    // NOTE Chains are wound the same as polygons, so mirroring walks them backwards
+    fn transform_chain(&self, chain: &EdgeChain) -> EdgeChain {
+        let [prev, next] = chain.ghosts().map(|v| v.map(|v| self.transform.transform_point2(v)));
+        if self.mirrored {
+            EdgeChain::new(self.transform_points(chain.points()), next, prev)
+        } else {
+            EdgeChain::new(self.transform_points(chain.points()), prev, next)
+        }
+    }
+
+    fn transform_points(&self, points: &[Vec2]) -> Vec<Vec2> {
+        let points = points.iter().map(|&v| self.transform.transform_point2(v));
+        if self.mirrored {
+            points.rev().collect()
+        } else {
+            points.collect()
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::math::{Affine2, Vec2};
+    use crate::prelude::{Ball, BoxAligned, BoxOriented, Capsule, PolygonSmall, Ramp, ShapeMoving, ShapeStatic, validate_convex_polygon};
+
+    fn is_near(a: Vec2, b: Vec2) -> bool {
+        (a - b).length() < 1.0e-4
+    }
+
+    #[test]
+    fn check_transform_boxes() {
+        let shape: ShapeStatic = BoxAligned::new(Vec2::new(1.0, 0.0), Vec2::new(2.0, 1.0)).into();
+
+        // Quarter turns stay aligned
+        let transform = Affine2::from_angle_translation(std::f32::consts::FRAC_PI_2, Vec2::new(0.0, 5.0));
+        let ShapeStatic::BoxAligned(s) = shape.transformed(&transform).unwrap() else { panic!("Expected aligned box") };
+        assert!(is_near(s.origin, Vec2::new(0.0, 6.0)));
+        assert!(is_near(s.size,   Vec2::new(1.0, 2.0)));
+
+        // Anything else becomes oriented
+        let transform = Affine2::from_scale_angle_translation(Vec2::new(2.0, 3.0), 0.5, Vec2::ZERO);
+        let ShapeStatic::BoxOriented(s) = shape.transformed(&transform).unwrap() else { panic!("Expected oriented box") };
+        assert!(is_near(s.size, Vec2::new(4.0, 3.0)));
+        assert!(is_near(s.direction, Vec2::from_angle(0.5)));
+
+        // Scaling an oriented box off its axes shears it
+        let shape: ShapeStatic = BoxOriented::new(Vec2::ZERO, Vec2::ONE, Vec2::from_angle(0.5)).into();
+        let ShapeStatic::PolygonSmall(s) = shape.transformed(&Affine2::from_scale(Vec2::new(1.0, 2.0))).unwrap() else { panic!("Expected polygon") };
+        assert!(validate_convex_polygon(s.points()).is_ok());
+
+        // The sheared box keeps its place, on top of being moved by the transform
+        let shape: ShapeStatic = BoxOriented::new(Vec2::new(10.0, 0.0), Vec2::ONE, Vec2::from_angle(0.5)).into();
+        let transform = Affine2::from_scale_angle_translation(Vec2::new(1.0, 2.0), 0.0, Vec2::new(0.0, 3.0));
+        let ShapeStatic::PolygonSmall(s) = shape.transformed(&transform).unwrap() else { panic!("Expected polygon") };
+        let center = s.points().iter().sum::<Vec2>()/s.points().len() as f32;
+        assert!(is_near(center, Vec2::new(10.0, 3.0)));
+        assert!(validate_convex_polygon(s.points()).is_ok());
+    }
+
+    #[test]
+    fn check_transform_mirrored() {
+        let mirror = Affine2::from_scale(Vec2::new(-1.0, 1.0));
+
+        let shape: ShapeStatic = PolygonSmall::new_from_points([Vec2::ZERO, Vec2::X, Vec2::Y]).into();
+        let ShapeStatic::PolygonSmall(s) = shape.transformed(&mirror).unwrap() else { panic!("Expected polygon") };
+        assert!(validate_convex_polygon(s.points()).is_ok());
+
+        // Ramps flip to face the other way
+        let shape: ShapeStatic = Ramp::new_from_size(Vec2::ZERO, Vec2::ONE, Vec2::new(2.0, 1.0)).into();
+        let ShapeStatic::Ramp(s) = shape.transformed(&mirror).unwrap() else { panic!("Expected ramp") };
+        let expected = Ramp::new_from_size(Vec2::ZERO, Vec2::new(-1.0, 1.0), Vec2::new(2.0, 1.0));
+        assert!(is_near(s.direction, expected.direction));
+        assert!((s.length - expected.length).abs() < 1.0e-4);
+        assert!(is_near(s.get_normal(), expected.get_normal()));
+    }
+
+    #[test]
+    fn check_transform_round() {
+        let transform = Affine2::from_scale_angle_translation(Vec2::splat(2.0), 1.0, Vec2::X);
+        let shape: ShapeMoving = Capsule::new_vertical(Vec2::ZERO, 2.0, 0.5).into();
+        let ShapeMoving::Capsule(s) = shape.transformed(&transform).unwrap() else { panic!("Expected capsule") };
+        assert!(is_near(s.origin, Vec2::X));
+        assert!((s.radius - 1.0).abs() < 1.0e-4);
+        assert!(is_near(s.direction, Vec2::from_angle(1.0).perp()));
+
+        let shape: ShapeMoving = Ball::new(Vec2::ZERO, 1.0).into();
+        assert!(shape.transformed(&Affine2::from_scale(Vec2::new(1.0, 2.0))).is_err());
+        assert!(shape.transformed(&Affine2::from_scale(Vec2::ZERO)).is_err());
+        assert!(is_near(shape.transformed(&transform).unwrap().origin(), Vec2::X));
+    }
+}
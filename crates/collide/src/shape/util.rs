@@ -3,6 +3,8 @@
 use bevy::prelude::{Vec2, Vec2Swizzles};
 use tinyvec::{array_vec, ArrayVec};
 
+use crate::prelude::{BoxAligned, ClosestPoint, POLYGON_MINKOWSKI_CAPACITY};
+
 pub(crate) fn get_polygon_data_for_ramp(direction: Vec2, length: f32) -> ([Vec2; 3], [Vec2; 3], [f32; 3]) {
     let size   = Vec2::new(direction.x, -direction.y) * length;
     let normal = direction.perp();
@@ -134,4 +136,182 @@ fn get_polygon_data_for_oriented_rect_rected_quick_impl(points: &[Vec2; 4], norm
     }
 
     result
-}
\ No newline at end of file
+}
+pub(crate) fn get_polygon_data_for_rect(size: Vec2, direction: Vec2) -> ([Vec2; 4], [Vec2; 4], [f32; 4]) {
+    (
+        [
+            Vec2::new( size.x,  size.y).rotate(direction),
+            Vec2::new(-size.x,  size.y).rotate(direction),
+            Vec2::new(-size.x, -size.y).rotate(direction),
+            Vec2::new( size.x, -size.y).rotate(direction),
+        ],
+        [
+             direction.perp(),
+            -direction,
+            -direction.perp(),
+             direction
+        ],
+        [
+            2.0*size.x,
+            2.0*size.y,
+            2.0*size.x,
+            2.0*size.y
+        ]
+    )
+}
+
+pub(crate) fn find_closest_on_circle(origin: Vec2, radius: f32, point: Vec2) -> ClosestPoint {
+    let offset   = point - origin;
+    let length   = offset.length();
+    let normal   = if length > 0.0 { offset/length } else { Vec2::Y };
+    ClosestPoint{
+        point:    origin + normal*radius,
+        normal,
+        distance: length - radius,
+    }
+}
+
+pub(crate) fn find_closest_on_polygon(origin: Vec2, points: &[Vec2], normals: &[Vec2], lengths: &[f32], radius: f32, point: Vec2) -> ClosestPoint {
+    let point = point - origin;
+
+    // Find the edge we're furthest in front of, if we're behind all of
+    // them then we're inside and that edge is the shortest way out. 
+    // Segments never count as inside, one of their edges always faces us.
+    let (idx, separation) = (0..points.len())
+        .map(|i| (i, normals[i].dot(point - points[i])))
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .unwrap();
+
+    if separation < 0.0 {
+        let normal = normals[idx];
+        return ClosestPoint{
+            point:    origin + point + normal*(radius - separation),
+            normal,
+            distance: separation - radius,
+        };
+    }
+
+    // Otherwise we're outside, so find the closest point on the edges
+    let (edge, closest, distance, on_face) = (0..points.len())
+        .map(|i| {
+            let along   = normals[i].perp().dot(point - points[i]);
+            let clamped = along.clamp(0.0, lengths[i]);
+            let closest = points[i] + normals[i].perp()*clamped;
+            // Segments have both sides on top of each other, so make sure we're on the front
+            let on_face = along == clamped && normals[i].dot(point - points[i]) >= 0.0;
+            (i, closest, closest.distance(point), on_face)
+        })
+        .min_by(|(_, _, a, _), (_, _, b, _)| a.total_cmp(b))
+        .unwrap();
+
+    // In front of an edge the normal is always the edge's, the direction to the 
+    // point is only needed for corners. That's also noisy when we're right on the
+    // surface, and undefined when we're exactly on it.
+    let normal = if on_face { 
+        normals[edge] 
+    } else if distance > 0.0 { 
+        (point - closest)/distance 
+    } else { 
+        normals[idx] 
+    };
+    ClosestPoint{
+        point:    origin + closest + normal*radius,
+        normal,
+        distance: distance - radius,
+    }
+}
+
+pub(crate) fn get_polygon_points_for_minkowski(a: &[Vec2], b: &[Vec2]) -> ArrayVec<[Vec2; POLYGON_MINKOWSKI_CAPACITY]> {
+    // Walk the edges of both polygons in order of angle, starting from their
+    // lowest points, the sum of the current points is the next point of the hull
+    let a_start = find_polygon_lowest_point(a);
+    let b_start = find_polygon_lowest_point(b);
+    let a_at = |i: usize| a[(a_start + i) % a.len()];
+    let b_at = |i: usize| b[(b_start + i) % b.len()];
+
+    let mut result = ArrayVec::default();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        result.push(a_at(i) + b_at(j));
+        let cross  = (a_at(i+1) - a_at(i)).perp_dot(b_at(j+1) - b_at(j));
+        let step_a = j >= b.len() || (i < a.len() && cross >= 0.0);
+        let step_b = i >= a.len() || (j < b.len() && cross <= 0.0);
+        if step_a { i += 1; }
+        if step_b { j += 1; }
+    }
+
+    result
+}
+
+fn find_polygon_lowest_point(points: &[Vec2]) -> usize {
+    (0..points.len()).min_by(|&a, &b| {
+        points[a].y.total_cmp(&points[b].y).then(points[a].x.total_cmp(&points[b].x))
+    }).unwrap()
+}
+
+// NOTE Edges go from points[i] along normals[i].perp(), this works for both 
+//      closed polygons and open chains (which have one less edge than points)
+pub(crate) fn find_closest_on_edges(points: &[Vec2], normals: &[Vec2], lengths: &[f32], point: Vec2) -> (usize, f32, Vec2) {
+    (0..lengths.len())
+        .map(|i| {
+            let along   = normals[i].perp().dot(point - points[i]).clamp(0.0, lengths[i]);
+            let closest = points[i] + normals[i].perp()*along;
+            (i, along, closest)
+        })
+        .min_by(|(_, _, a), (_, _, b)| a.distance_squared(point).total_cmp(&b.distance_squared(point)))
+        .unwrap()
+}
+
+pub(crate) fn is_point_in_polygon(points: &[Vec2], point: Vec2) -> bool {
+    // Crossing test, works for concave polygons too
+    let mut inside = false;
+    for i in 0..points.len() {
+        let a = points[i];
+        let b = points[(i+1)%points.len()];
+        if (a.y > point.y) != (b.y > point.y) && point.x < a.x + (point.y - a.y)*(b.x - a.x)/(b.y - a.y) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+pub(crate) fn get_polygon_data_for_points(points: &[Vec2], closed: bool) -> (Box<[Vec2]>, Box<[f32]>, BoxAligned) {
+    let edges = if closed { points.len() } else { points.len().saturating_sub(1) };
+    let mut normals = Vec::with_capacity(edges);
+    let mut lengths = Vec::with_capacity(edges);
+
+    let mut min = Vec2::MAX;
+    let mut max = Vec2::MIN;
+
+    for (i, &start) in points.iter().enumerate() {
+        min = min.min(start);
+        max = max.max(start);
+
+        if i < edges {
+            let end    = points[(i+1)%points.len()];
+            let offset = end-start;
+            let length = offset.length();
+            lengths.push(length);
+            normals.push(-(offset/length).perp());
+        }
+    }
+
+    let origin = (min + max)*0.5;
+    let size   = max - origin;
+    (normals.into_boxed_slice(), lengths.into_boxed_slice(), BoxAligned::new(origin, size))
+}
+
+// NOTE A vertex is only a corner if the chain turns away from the solid side 
+//      there, flat and concave joins are covered by the edges either side.
+//      The ends of a chain are corners unless we know what they join onto.
+pub(crate) fn get_corners_for_points(points: &[Vec2], ghost_prev: Option<Vec2>, ghost_next: Option<Vec2>) -> Box<[bool]> {
+    let last = points.len() - 1;
+    (0..points.len()).map(|i| {
+        let prev = if i == 0    { ghost_prev } else { Some(points[i-1]) };
+        let next = if i == last { ghost_next } else { Some(points[i+1]) };
+        match (prev, next) {
+            (Some(prev), Some(next)) => (points[i] - prev).perp_dot(next - points[i]) > 0.0,
+            _ => true,
+        }
+    }).collect()
+}
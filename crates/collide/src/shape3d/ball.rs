@@ -0,0 +1,37 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use bevy::prelude::Vec3;
+
+use crate::prelude3d::{BoxAligned, RayTarget, RayCaster, RayIntersection, ShapeCommon};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Ball {
+    pub origin: Vec3,
+    pub radius: f32,
+}
+
+impl Ball {
+    pub fn new(origin: Vec3, radius: f32) -> Self {
+        Self{origin, radius}
+    }
+}
+
+impl ShapeCommon for Ball {
+    fn bounding_box(&self) -> BoxAligned {
+        BoxAligned::new(self.origin, Vec3::splat(self.radius))
+    }
+
+    fn origin(&self) -> Vec3 {
+        self.origin
+    }
+
+    fn set_origin(&mut self, origin: Vec3) {
+        self.origin = origin;
+    }
+}
+
+impl RayTarget for Ball {
+    fn raycast(&self, ray: &RayCaster) -> Option<[RayIntersection; 2]> {
+        ray.test_sphere(self.origin, self.radius)
+    }
+}
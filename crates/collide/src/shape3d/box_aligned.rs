@@ -0,0 +1,48 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use bevy::prelude::{Quat, Vec3};
+
+use crate::prelude3d::{RayTarget, RayCaster, RayIntersection, ShapeCommon, get_planes_for_box, get_points_for_box};
+
+#[derive(Debug, Clone, Copy)]
+pub struct BoxAligned {
+    pub origin: Vec3,
+    pub size:   Vec3,
+}
+
+impl BoxAligned {
+    pub fn new(origin: Vec3, size: Vec3) -> Self {
+        Self{origin, size}
+    }
+
+    pub fn bounds(&self) -> [Vec3; 2] {
+        [
+            self.origin - self.size,
+            self.origin + self.size,
+        ]
+    }
+
+    pub fn get_points(&self) -> [Vec3; 8] {
+        get_points_for_box(self.origin, self.size, Quat::IDENTITY)
+    }
+}
+
+impl ShapeCommon for BoxAligned {
+    fn bounding_box(&self) -> BoxAligned {
+        *self
+    }
+
+    fn origin(&self) -> Vec3 {
+        self.origin
+    }
+
+    fn set_origin(&mut self, origin: Vec3) {
+        self.origin = origin;
+    }
+}
+
+impl RayTarget for BoxAligned {
+    fn raycast(&self, ray: &RayCaster) -> Option<[RayIntersection; 2]> {
+        ray.test_planes(&get_planes_for_box(self.origin, self.size, Quat::IDENTITY))
+    }
+}
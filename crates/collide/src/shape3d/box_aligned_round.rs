@@ -0,0 +1,42 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use bevy::prelude::{Quat, Vec3};
+
+use crate::prelude3d::{BoxAligned, RayTarget, RayCaster, RayIntersection, ShapeCommon, get_points_for_box};
+
+#[derive(Debug, Clone, Copy)]
+pub struct BoxAlignedRound {
+    pub origin: Vec3,
+    pub size:   Vec3,
+    pub radius: f32,
+}
+
+impl BoxAlignedRound {
+    pub fn new(origin: Vec3, size: Vec3, radius: f32) -> Self {
+        Self{origin, size, radius}
+    }
+
+    pub fn get_points(&self) -> [Vec3; 8] {
+        get_points_for_box(self.origin, self.size, Quat::IDENTITY)
+    }
+}
+
+impl ShapeCommon for BoxAlignedRound {
+    fn bounding_box(&self) -> BoxAligned {
+        BoxAligned::new(self.origin, self.size + Vec3::splat(self.radius))
+    }
+
+    fn origin(&self) -> Vec3 {
+        self.origin
+    }
+
+    fn set_origin(&mut self, origin: Vec3) {
+        self.origin = origin;
+    }
+}
+
+impl RayTarget for BoxAlignedRound {
+    fn raycast(&self, ray: &RayCaster) -> Option<[RayIntersection; 2]> {
+        ray.test_hull_rounded(&self.get_points(), self.radius)
+    }
+}
@@ -0,0 +1,42 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use bevy::prelude::{Quat, Vec3};
+
+use crate::prelude3d::{BoxAligned, RayTarget, RayCaster, RayIntersection, ShapeCommon, get_bounds_for_points, get_planes_for_box, get_points_for_box};
+
+#[derive(Debug, Clone, Copy)]
+pub struct BoxOriented {
+    pub origin:   Vec3,
+    pub size:     Vec3,
+    pub rotation: Quat,
+}
+
+impl BoxOriented {
+    pub fn new(origin: Vec3, size: Vec3, rotation: Quat) -> Self {
+        Self{origin, size, rotation}
+    }
+
+    pub fn get_points(&self) -> [Vec3; 8] {
+        get_points_for_box(self.origin, self.size, self.rotation)
+    }
+}
+
+impl ShapeCommon for BoxOriented {
+    fn bounding_box(&self) -> BoxAligned {
+        get_bounds_for_points(&self.get_points(), 0.0)
+    }
+
+    fn origin(&self) -> Vec3 {
+        self.origin
+    }
+
+    fn set_origin(&mut self, origin: Vec3) {
+        self.origin = origin;
+    }
+}
+
+impl RayTarget for BoxOriented {
+    fn raycast(&self, ray: &RayCaster) -> Option<[RayIntersection; 2]> {
+        ray.test_planes(&get_planes_for_box(self.origin, self.size, self.rotation))
+    }
+}
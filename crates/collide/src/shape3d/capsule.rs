@@ -0,0 +1,52 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use bevy::prelude::Vec3;
+
+use crate::prelude3d::{BoxAligned, RayTarget, RayCaster, RayIntersection, ShapeCommon};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Capsule {
+    pub origin:    Vec3,
+    pub direction: Vec3,
+    pub length:    f32,
+    pub radius:    f32,
+}
+
+impl Capsule {
+    pub fn new(origin: Vec3, direction: Vec3, length: f32, radius: f32) -> Self {
+        Self{origin, direction, length, radius}
+    }
+
+    pub fn new_vertical(origin: Vec3, height: f32, radius: f32) -> Self {
+        Self::new(origin, Vec3::Y, (height - 2.0*radius).max(0.0), radius)
+    }
+
+    pub fn get_points(&self) -> [Vec3; 2] {
+        let offset = self.direction*self.length*0.5;
+        [
+            self.origin - offset,
+            self.origin + offset,
+        ]
+    }
+}
+
+impl ShapeCommon for Capsule {
+    fn bounding_box(&self) -> BoxAligned {
+        let size = (self.direction*self.length*0.5).abs();
+        BoxAligned::new(self.origin, size + Vec3::splat(self.radius))
+    }
+
+    fn origin(&self) -> Vec3 {
+        self.origin
+    }
+
+    fn set_origin(&mut self, origin: Vec3) {
+        self.origin = origin;
+    }
+}
+
+impl RayTarget for Capsule {
+    fn raycast(&self, ray: &RayCaster) -> Option<[RayIntersection; 2]> {
+        ray.test_hull_rounded(&self.get_points(), self.radius)
+    }
+}
@@ -0,0 +1,64 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use bevy::prelude::Vec3;
+use tinyvec::ArrayVec;
+
+use crate::prelude3d::{BoxAligned, RayTarget, RayCaster, RayIntersection, ShapeCommon, HULL_CAPACITY, find_support, get_bounds_for_points};
+
+// NOTE Unlike the 2D polygon we never build the sum, its support is just the
+//      sum of both hulls' supports, which is all the hull tests need.
+
+#[derive(Debug, Clone, Copy)]
+pub struct HullMinkowski {
+    a:      ArrayVec<[Vec3; HULL_CAPACITY]>,
+    b:      ArrayVec<[Vec3; HULL_CAPACITY]>,
+    bounds: BoxAligned,
+    radius: f32,
+}
+
+impl HullMinkowski {
+    pub fn new(a: &[Vec3], b: &[Vec3], radius: f32) -> Self {
+        let bounds_a = get_bounds_for_points(a, 0.0);
+        let bounds_b = get_bounds_for_points(b, 0.0);
+        Self{
+            a:      a.iter().copied().collect(),
+            b:      b.iter().copied().collect(),
+            bounds: BoxAligned::new(bounds_a.origin + bounds_b.origin, bounds_a.size + bounds_b.size),
+            radius,
+        }
+    }
+
+    pub fn find_support(&self, direction: Vec3) -> Vec3 {
+        find_support(&self.a, direction) + find_support(&self.b, direction)
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+}
+
+impl ShapeCommon for HullMinkowski {
+    fn bounding_box(&self) -> BoxAligned {
+        let mut bounds = self.bounds;
+        bounds.size += Vec3::splat(self.radius);
+        bounds
+    }
+
+    fn origin(&self) -> Vec3 {
+        self.bounds.origin
+    }
+
+    fn set_origin(&mut self, origin: Vec3) {
+        let delta = origin - self.bounds.origin;
+        for point in self.b.iter_mut() {
+            *point += delta;
+        }
+        self.bounds.origin = origin;
+    }
+}
+
+impl RayTarget for HullMinkowski {
+    fn raycast(&self, ray: &RayCaster) -> Option<[RayIntersection; 2]> {
+        ray.test_support_rounded(|direction| self.find_support(direction), self.radius)
+    }
+}
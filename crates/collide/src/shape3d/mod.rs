@@ -0,0 +1,64 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+// // Ball // //
+
+mod ball;
+pub use ball::*;
+
+// // Capsule // //
+
+mod capsule;
+pub use capsule::*;
+
+// // Box Aligned // //
+
+mod box_aligned;
+pub use box_aligned::*;
+
+mod box_aligned_round;
+pub use box_aligned_round::*;
+
+// // Box Oriented // //
+
+mod box_oriented;
+pub use box_oriented::*;
+
+mod box_oriented_round;
+pub use box_oriented_round::*;
+
+// // Ramp // //
+
+mod ramp;
+pub use ramp::*;
+
+mod ramp_round;
+pub use ramp_round::*;
+
+// // Hull // //
+
+mod hull_minkowski;
+pub use hull_minkowski::*;
+
+// // Shape Types // //
+
+mod shape_static;
+pub use shape_static::*;
+
+mod shape_moving;
+pub use shape_moving::*;
+
+mod shape_combined;
+pub use shape_combined::*;
+
+mod shape_overlap;
+
+mod shape_impact;
+pub use shape_impact::*;
+
+// // Misc // //
+
+mod shape_common;
+pub use shape_common::*;
+
+mod util;
+pub(crate) use util::*;
@@ -0,0 +1,49 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use bevy::prelude::{Quat, Vec3};
+
+use crate::prelude3d::{BoxAligned, RayTarget, RayCaster, RayIntersection, ShapeCommon, get_bounds_for_points, get_normal_for_ramp, get_planes_for_ramp, get_points_for_ramp};
+
+// NOTE A wedge, the size is the half extents of the box it's cut from. Turn it
+//      around Y to change which way it rises.
+
+#[derive(Debug, Clone, Copy)]
+pub struct Ramp {
+    pub origin:   Vec3,
+    pub size:     Vec3,
+    pub rotation: Quat,
+}
+
+impl Ramp {
+    pub fn new(origin: Vec3, size: Vec3, rotation: Quat) -> Self {
+        Self{origin, size, rotation}
+    }
+
+    pub fn get_normal(&self) -> Vec3 {
+        get_normal_for_ramp(self.size, self.rotation)
+    }
+
+    pub fn get_points(&self) -> [Vec3; 6] {
+        get_points_for_ramp(self.origin, self.size, self.rotation)
+    }
+}
+
+impl ShapeCommon for Ramp {
+    fn bounding_box(&self) -> BoxAligned {
+        get_bounds_for_points(&self.get_points(), 0.0)
+    }
+
+    fn origin(&self) -> Vec3 {
+        self.origin
+    }
+
+    fn set_origin(&mut self, origin: Vec3) {
+        self.origin = origin;
+    }
+}
+
+impl RayTarget for Ramp {
+    fn raycast(&self, ray: &RayCaster) -> Option<[RayIntersection; 2]> {
+        ray.test_planes(&get_planes_for_ramp(self.origin, self.size, self.rotation))
+    }
+}
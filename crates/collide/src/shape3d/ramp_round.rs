@@ -0,0 +1,47 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use bevy::prelude::{Quat, Vec3};
+
+use crate::prelude3d::{BoxAligned, RayTarget, RayCaster, RayIntersection, ShapeCommon, get_bounds_for_points, get_normal_for_ramp, get_points_for_ramp};
+
+#[derive(Debug, Clone, Copy)]
+pub struct RampRound {
+    pub origin:   Vec3,
+    pub size:     Vec3,
+    pub rotation: Quat,
+    pub radius:   f32,
+}
+
+impl RampRound {
+    pub fn new(origin: Vec3, size: Vec3, rotation: Quat, radius: f32) -> Self {
+        Self{origin, size, rotation, radius}
+    }
+
+    pub fn get_normal(&self) -> Vec3 {
+        get_normal_for_ramp(self.size, self.rotation)
+    }
+
+    pub fn get_points(&self) -> [Vec3; 6] {
+        get_points_for_ramp(self.origin, self.size, self.rotation)
+    }
+}
+
+impl ShapeCommon for RampRound {
+    fn bounding_box(&self) -> BoxAligned {
+        get_bounds_for_points(&self.get_points(), self.radius)
+    }
+
+    fn origin(&self) -> Vec3 {
+        self.origin
+    }
+
+    fn set_origin(&mut self, origin: Vec3) {
+        self.origin = origin;
+    }
+}
+
+impl RayTarget for RampRound {
+    fn raycast(&self, ray: &RayCaster) -> Option<[RayIntersection; 2]> {
+        ray.test_hull_rounded(&self.get_points(), self.radius)
+    }
+}
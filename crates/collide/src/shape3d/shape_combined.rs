@@ -0,0 +1,137 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use bevy::math::Vec3;
+use macro_attr_2018::macro_attr;
+use enum_derive_2018::EnumFromInner;
+use tinyvec::ArrayVec;
+
+use crate::prelude3d::*;
+
+macro_attr! {
+    #[derive(EnumFromInner!)]
+    pub enum ShapeCombined {
+        Ball(Ball),
+
+        BoxAligned(BoxAligned),
+        BoxAlignedRound(BoxAlignedRound),
+        BoxOrientedRound(BoxOrientedRound),
+
+        RampRound(RampRound),
+        Capsule(Capsule),
+        HullMinkowski(HullMinkowski),
+    }
+}
+
+impl ShapeCombined {
+
+    pub(crate) fn find_support(&self, direction: Vec3) -> Vec3 {
+        match self {
+            ShapeCombined::Ball(s)             => s.origin,
+            ShapeCombined::BoxAligned(s)       => find_support(&s.get_points(), direction),
+            ShapeCombined::BoxAlignedRound(s)  => find_support(&s.get_points(), direction),
+            ShapeCombined::BoxOrientedRound(s) => find_support(&s.get_points(), direction),
+            ShapeCombined::RampRound(s)        => find_support(&s.get_points(), direction),
+            ShapeCombined::Capsule(s)          => find_support(&s.get_points(), direction),
+            ShapeCombined::HullMinkowski(s)    => s.find_support(direction),
+        }
+    }
+
+    // NOTE Closest point on the shape without its rounding
+    pub(crate) fn find_closest(&self, point: Vec3) -> (Vec3, f32) {
+        find_closest_on_hull(&|direction| self.find_support(direction), point)
+    }
+
+}
+
+impl ShapeCommon for ShapeCombined {
+    fn bounding_box(&self) -> BoxAligned {
+        match self {
+            ShapeCombined::Ball(s)             => s.bounding_box(),
+            ShapeCombined::BoxAligned(s)       => s.bounding_box(),
+            ShapeCombined::BoxAlignedRound(s)  => s.bounding_box(),
+            ShapeCombined::BoxOrientedRound(s) => s.bounding_box(),
+            ShapeCombined::RampRound(s)        => s.bounding_box(),
+            ShapeCombined::Capsule(s)          => s.bounding_box(),
+            ShapeCombined::HullMinkowski(s)    => s.bounding_box(),
+        }
+    }
+
+    fn origin(&self) -> Vec3 {
+        match self {
+            ShapeCombined::Ball(s)             => s.origin(),
+            ShapeCombined::BoxAligned(s)       => s.origin(),
+            ShapeCombined::BoxAlignedRound(s)  => s.origin(),
+            ShapeCombined::BoxOrientedRound(s) => s.origin(),
+            ShapeCombined::RampRound(s)        => s.origin(),
+            ShapeCombined::Capsule(s)          => s.origin(),
+            ShapeCombined::HullMinkowski(s)    => s.origin(),
+        }
+    }
+
+    fn set_origin(&mut self, origin: Vec3) {
+        match self {
+            ShapeCombined::Ball(s)             => s.set_origin(origin),
+            ShapeCombined::BoxAligned(s)       => s.set_origin(origin),
+            ShapeCombined::BoxAlignedRound(s)  => s.set_origin(origin),
+            ShapeCombined::BoxOrientedRound(s) => s.set_origin(origin),
+            ShapeCombined::RampRound(s)        => s.set_origin(origin),
+            ShapeCombined::Capsule(s)          => s.set_origin(origin),
+            ShapeCombined::HullMinkowski(s)    => s.set_origin(origin),
+        }
+    }
+}
+
+impl RayTarget for ShapeCombined {
+    fn raycast(&self, ray: &RayCaster) -> Option<[RayIntersection; 2]> {
+        match self {
+            ShapeCombined::Ball(s)             => s.raycast(ray),
+            ShapeCombined::BoxAligned(s)       => s.raycast(ray),
+            ShapeCombined::BoxAlignedRound(s)  => s.raycast(ray),
+            ShapeCombined::BoxOrientedRound(s) => s.raycast(ray),
+            ShapeCombined::RampRound(s)        => s.raycast(ray),
+            ShapeCombined::Capsule(s)          => s.raycast(ray),
+            ShapeCombined::HullMinkowski(s)    => s.raycast(ray),
+        }
+    }
+}
+
+impl ShapeCombined {
+
+    pub fn between_moving_and_static(a: &ShapeMoving, b: &ShapeStatic) -> Self {
+        match (a, b) {
+            (ShapeMoving::Ball(a),       ShapeStatic::Ball(b)           ) => Ball::new(b.origin, a.radius + b.radius).into(),
+            (ShapeMoving::Ball(a),       ShapeStatic::BoxAligned(b)     ) => BoxAlignedRound::new(b.origin, b.size, a.radius).into(),
+            (ShapeMoving::Ball(a),       ShapeStatic::BoxAlignedRound(b)) => BoxAlignedRound::new(b.origin, b.size, b.radius + a.radius).into(),
+            (ShapeMoving::BoxAligned(a), ShapeStatic::Ball(b)           ) => BoxAlignedRound::new(b.origin, a.size, b.radius).into(),
+            (ShapeMoving::BoxAligned(a), ShapeStatic::BoxAligned(b)     ) => BoxAligned::new(b.origin, a.size + b.size).into(),
+            (ShapeMoving::BoxAligned(a), ShapeStatic::BoxAlignedRound(b)) => BoxAlignedRound::new(b.origin, a.size + b.size, b.radius).into(),
+
+            (ShapeMoving::Ball(a),        ShapeStatic::BoxOriented(b)     ) => BoxOrientedRound::new(b.origin, b.size, b.rotation, a.radius).into(),
+            (ShapeMoving::Ball(a),        ShapeStatic::BoxOrientedRound(b)) => BoxOrientedRound::new(b.origin, b.size, b.rotation, b.radius + a.radius).into(),
+            (ShapeMoving::BoxOriented(a), ShapeStatic::Ball(b)            ) => BoxOrientedRound::new(b.origin, a.size, a.rotation, b.radius).into(),
+
+            (ShapeMoving::Ball(a), ShapeStatic::Ramp(b)     ) => RampRound::new(b.origin, b.size, b.rotation, a.radius).into(),
+            (ShapeMoving::Ball(a), ShapeStatic::RampRound(b)) => RampRound::new(b.origin, b.size, b.rotation, b.radius + a.radius).into(),
+
+            (ShapeMoving::Ball(a),    ShapeStatic::Capsule(b)) => Capsule::new(b.origin, b.direction, b.length, b.radius + a.radius).into(),
+            (ShapeMoving::Capsule(a), ShapeStatic::Ball(b)   ) => Capsule::new(b.origin, a.direction, a.length, a.radius + b.radius).into(),
+
+            _ => Self::between_convex_hulls(a, b.get_convex_hull()),
+        }
+    }
+
+    // NOTE Every moving shape is a static one too, and the sums are the same
+    pub fn between_moving(a: &ShapeMoving, b: &ShapeMoving) -> Self {
+        Self::between_moving_and_static(a, &(*b).into())
+    }
+
+    fn between_convex_hulls(a: &ShapeMoving, (b_points, b_radius): (ArrayVec<[Vec3; HULL_CAPACITY]>, f32)) -> Self {
+        // The sum is taken against the mover reflected through its origin,
+        // so that its origin sits inside the result whenever they overlap
+        let (a_points, a_radius) = a.get_convex_hull();
+        let a_origin = a.origin();
+        let a_points: ArrayVec<[Vec3; HULL_CAPACITY]> = a_points.iter().map(|&v| a_origin - v).collect();
+        HullMinkowski::new(&a_points, &b_points, a_radius + b_radius).into()
+    }
+
+}
@@ -0,0 +1,11 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use bevy::prelude::Vec3;
+
+use crate::prelude3d::BoxAligned;
+
+pub trait ShapeCommon {
+    fn bounding_box(&self) -> BoxAligned;
+    fn origin(&self) -> Vec3;
+    fn set_origin(&mut self, origin: Vec3);
+}
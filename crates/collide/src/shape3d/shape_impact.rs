@@ -0,0 +1,287 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use bevy::prelude::{Vec2, Vec3};
+
+use crate::prelude3d::{RayCaster, ShapeCombined, ShapeMoving, ShapeStatic};
+
+#[derive(Debug, Clone, Copy)]
+pub struct ShapeImpact {
+    pub time:   f32,
+    pub point:  Vec3,
+    pub normal: Vec3,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ShapeCast {
+    pub distance: f32,
+    pub origin:   Vec3,
+    pub point:    Vec3,
+    pub normal:   Vec3,
+}
+
+impl ShapeMoving {
+
+    pub fn find_impact_static(&self, velocity: Vec3, other: &ShapeStatic, max_time: f32) -> Option<ShapeImpact> {
+        let combined = ShapeCombined::between_moving_and_static(self, other);
+        let impact   = find_time_of_impact(&combined, self.origin(), velocity, max_time)?;
+        Some(find_impact_contact(
+            self.get_convex_hull(),
+            other.get_convex_hull(),
+            velocity*impact.0,
+            Vec3::ZERO,
+            impact
+        ))
+    }
+
+    pub fn find_impact_moving(&self, velocity: Vec3, other: &ShapeMoving, other_velocity: Vec3, max_time: f32) -> Option<ShapeImpact> {
+        let combined = ShapeCombined::between_moving(self, other);
+        let impact   = find_time_of_impact(&combined, self.origin(), velocity - other_velocity, max_time)?;
+        Some(find_impact_contact(
+            self.get_convex_hull(),
+            other.get_convex_hull(),
+            velocity*impact.0,
+            other_velocity*impact.0,
+            impact
+        ))
+    }
+
+}
+
+// NOTE The sweep through the combined shape only gives where our origin stops,
+//      the contact point comes from both hulls once they're placed there.
+
+impl ShapeMoving {
+
+    pub fn cast_static(&self, direction: Vec3, max_dist: f32, other: &ShapeStatic) -> Option<ShapeCast> {
+        let (distance, origin, normal) = find_cast(&ShapeCombined::between_moving_and_static(self, other), self.origin(), direction, max_dist)?;
+        Some(ShapeCast{
+            distance,
+            origin,
+            point: self.find_contact_static(origin - self.origin(), normal, other),
+            normal,
+        })
+    }
+
+    pub fn cast_moving(&self, direction: Vec3, max_dist: f32, other: &ShapeMoving) -> Option<ShapeCast> {
+        let (distance, origin, normal) = find_cast(&ShapeCombined::between_moving(self, other), self.origin(), direction, max_dist)?;
+        Some(ShapeCast{
+            distance,
+            origin,
+            point: self.find_contact_moving(origin - self.origin(), normal, other),
+            normal,
+        })
+    }
+
+    pub fn find_contact_static(&self, offset: Vec3, normal: Vec3, other: &ShapeStatic) -> Vec3 {
+        find_impact_contact(self.get_convex_hull(), other.get_convex_hull(), offset, Vec3::ZERO, (0.0, normal)).point
+    }
+
+    pub fn find_contact_moving(&self, offset: Vec3, normal: Vec3, other: &ShapeMoving) -> Vec3 {
+        find_impact_contact(self.get_convex_hull(), other.get_convex_hull(), offset, Vec3::ZERO, (0.0, normal)).point
+    }
+
+}
+
+// NOTE If we start overlapping then the impact is immediate, and casts stop
+//      where they are. We can only find the way out while we're within the
+//      rounding, past that we fall back to pushing back against the velocity.
+
+fn find_time_of_impact(combined: &ShapeCombined, origin: Vec3, velocity: Vec3, max_time: f32) -> Option<(f32, Vec3)> {
+    let speed = velocity.length();
+    let (distance, _, normal) = find_cast(combined, origin, velocity, speed*max_time)?;
+    if speed == 0.0 {
+        Some((0.0, find_overlap_normal(combined, origin, velocity)))
+    } else {
+        Some((distance/speed, normal))
+    }
+}
+
+fn find_cast(combined: &ShapeCombined, origin: Vec3, direction: Vec3, max_dist: f32) -> Option<(f32, Vec3, Vec3)> {
+    let ray = RayCaster::new_segment(origin, direction, 0.0, max_dist);
+    let [enter, _] = ray.test(combined)?;
+    if enter.distance < 0.0 {
+        Some((0.0, origin, find_overlap_normal(combined, origin, direction)))
+    } else {
+        Some((enter.distance, enter.point, enter.normal))
+    }
+}
+
+fn find_overlap_normal(combined: &ShapeCombined, origin: Vec3, direction: Vec3) -> Vec3 {
+    let (closest, distance) = combined.find_closest(origin);
+    if distance > 0.0 {
+        (origin - closest)/distance
+    } else {
+        (-direction).try_normalize().unwrap_or(Vec3::Y)
+    }
+}
+
+// NOTE The touching features (a face, edge or vertex of each hull) are flattened
+//      onto the contact plane, the contact is the middle of where their bounds
+//      overlap along both of its axes. Faces meeting give the middle of a patch.
+
+fn find_impact_contact(
+    (points_a, _): (impl AsRef<[Vec3]>, f32),
+    (points_b, radius_b): (impl AsRef<[Vec3]>, f32),
+    offset_a: Vec3,
+    offset_b: Vec3,
+    (time, normal): (f32, Vec3),
+) -> ShapeImpact {
+    let tangents = normal.any_orthonormal_pair();
+    let (a_min, a_max, _)       = find_support_feature(points_a.as_ref(), offset_a, -normal, tangents);
+    let (b_min, b_max, b_depth) = find_support_feature(points_b.as_ref(), offset_b,  normal, tangents);
+    let along = 0.5*(a_min.max(b_min) + a_max.min(b_max));
+
+    ShapeImpact{
+        time,
+        point: tangents.0*along.x + tangents.1*along.y + normal*(b_depth + radius_b),
+        normal,
+    }
+}
+
+fn find_support_feature(points: &[Vec3], offset: Vec3, direction: Vec3, tangents: (Vec3, Vec3)) -> (Vec2, Vec2, f32) {
+    const EPSILON: f32 = 1.0e-4;
+    let depth = points.iter().map(|&v| (v + offset).dot(direction)).fold(-f32::INFINITY, f32::max);
+    let (min, max) = points.iter()
+        .filter(|&&v| (v + offset).dot(direction) >= depth - EPSILON)
+        .map(|&v| Vec2::new((v + offset).dot(tangents.0), (v + offset).dot(tangents.1)))
+        .fold((Vec2::INFINITY, -Vec2::INFINITY), |(min, max), v| (min.min(v), max.max(v)));
+    (min, max, depth)
+}
+
+#[cfg(test)]
+mod test {
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, SQRT_2};
+
+    use bevy::prelude::{Quat, Vec3};
+    use crate::prelude3d::{Ball, BoxAligned, BoxOriented, Capsule, Ramp, ShapeMoving, ShapeStatic};
+
+    fn is_near(a: Vec3, b: Vec3) -> bool {
+        (a - b).length() < 1.0e-3
+    }
+
+    #[test]
+    fn check_cast() {
+        let ground: ShapeStatic = BoxAligned::new(Vec3::ZERO, Vec3::new(2.0, 0.5, 2.0)).into();
+
+        let mover: ShapeMoving = BoxAligned::new(Vec3::new(1.5, 3.0, 0.0), Vec3::splat(0.5)).into();
+        let cast = mover.cast_static(-Vec3::Y, 10.0, &ground).unwrap();
+        assert!((cast.distance - 2.0).abs() < 1.0e-5);
+        assert!(is_near(cast.origin, Vec3::new(1.5, 1.0, 0.0)));
+        assert!(is_near(cast.point,  Vec3::new(1.5, 0.5, 0.0)));
+
+        // Hanging off the edge, we touch the edge of the ground
+        let mover: ShapeMoving = Ball::new(Vec3::new(2.3, 3.0, 0.0), 0.5).into();
+        let cast = mover.cast_static(-Vec3::Y, 10.0, &ground).unwrap();
+        assert!(is_near(cast.point, Vec3::new(2.0, 0.5, 0.0)));
+        assert!(((cast.origin - cast.point).length() - 0.5).abs() < 1.0e-3);
+        assert!(mover.cast_static(-Vec3::Y, 1.0, &ground).is_none());
+
+        // Capsules against boxes go through the general hull sum
+        let mover: ShapeMoving = Capsule::new_vertical(Vec3::new(-5.0, 1.0, 0.0), 2.0, 0.5).into();
+        let cast = mover.cast_static(Vec3::X, 10.0, &BoxAligned::new(Vec3::new(0.0, 1.0, 0.0), Vec3::splat(1.0)).into()).unwrap();
+        assert!((cast.distance - 3.5).abs() < 1.0e-3);
+        assert!(is_near(cast.normal, -Vec3::X));
+        assert!(is_near(cast.point, Vec3::new(-1.0, 1.0, 0.0)));
+
+        // Sliding down onto a ramp, we stop on the slope
+        let ramp: ShapeStatic = Ramp::new(Vec3::ZERO, Vec3::ONE, Quat::IDENTITY).into();
+        let mover: ShapeMoving = Ball::new(Vec3::new(0.0, 5.0, 0.0), 0.5).into();
+        let cast = mover.cast_static(-Vec3::Y, 10.0, &ramp).unwrap();
+        assert!(is_near(cast.normal, Vec3::new(-1.0, 1.0, 0.0).normalize()));
+        assert!((cast.distance - (5.0 - 0.5*SQRT_2)).abs() < 1.0e-3);
+
+        // Starting in the rounding, we don't move and get pushed out of the top
+        let mover: ShapeMoving = Ball::new(Vec3::new(0.5, 0.8, 0.0), 0.5).into();
+        let cast = mover.cast_static(Vec3::X, 10.0, &ground).unwrap();
+        assert_eq!(cast.distance, 0.0);
+        assert_eq!(cast.origin, Vec3::new(0.5, 0.8, 0.0));
+        assert!(is_near(cast.normal, Vec3::Y));
+        assert!(is_near(cast.point,  Vec3::new(0.5, 0.5, 0.0)));
+
+        // Deep inside, we push back against the cast instead
+        let rotation = Quat::from_rotation_x(0.3)*Quat::from_rotation_y(0.7);
+        let obstacle: ShapeStatic = BoxOriented::new(Vec3::ZERO, Vec3::splat(1.0), rotation).into();
+        let mover: ShapeMoving = BoxAligned::new(Vec3::new(0.1, 0.2, 0.0), Vec3::splat(0.5)).into();
+        let cast = mover.cast_static(Vec3::X, 10.0, &obstacle).unwrap();
+        assert_eq!(cast.distance, 0.0);
+        assert_eq!(cast.origin, Vec3::new(0.1, 0.2, 0.0));
+        assert!(is_near(cast.normal, -Vec3::X));
+
+        let other: ShapeMoving = Ball::new(Vec3::new(1.0, 0.0, 0.0), 0.5).into();
+        let mover: ShapeMoving = Ball::new(Vec3::new(0.6, 0.0, 0.0), 0.5).into();
+        let cast = mover.cast_moving(Vec3::X, 10.0, &other).unwrap();
+        assert_eq!(cast.distance, 0.0);
+        assert!(is_near(cast.normal, -Vec3::X));
+    }
+
+    #[test]
+    fn check_impact() {
+        let a: ShapeMoving = Ball::new(Vec3::new(-2.0, 0.0, 0.0), 0.5).into();
+        let b: ShapeMoving = Ball::new(Vec3::new( 2.0, 0.0, 0.0), 0.5).into();
+        let impact = a.find_impact_moving(Vec3::X, &b, -Vec3::X, 10.0).unwrap();
+        assert!((impact.time - 1.5).abs() < 1.0e-5);
+        assert!(is_near(impact.point, Vec3::ZERO));
+        assert!(is_near(impact.normal, -Vec3::X));
+
+        assert!(a.find_impact_moving(Vec3::X, &b, Vec3::X, 10.0).is_none());
+
+        // Landing across the corner of a wider crate, the faces overlap in a patch
+        // that's clipped along both axes of the contact plane
+        let a: ShapeMoving = BoxAligned::new(Vec3::new(1.25, 3.0, -1.25), Vec3::splat(0.5)).into();
+        let b: ShapeMoving = BoxAligned::new(Vec3::ZERO, Vec3::new(1.0, 0.5, 1.0)).into();
+        let impact = a.find_impact_moving(-Vec3::Y, &b, Vec3::Y, 10.0).unwrap();
+        assert!((impact.time - 1.0).abs() < 1.0e-5);
+        assert!(is_near(impact.point,  Vec3::new(0.875, 1.5, -0.875)));
+        assert!(is_near(impact.normal, Vec3::Y));
+    }
+
+    #[test]
+    fn check_cast_rotated() {
+        // Tipped onto an edge about X, so the top is a ridge running along X
+        let ridge: ShapeStatic = BoxOriented::new(Vec3::ZERO, Vec3::ONE, Quat::from_rotation_x(FRAC_PI_4)).into();
+        let mover: ShapeMoving = Ball::new(Vec3::new(0.3, 5.0, 0.0), 0.5).into();
+        let cast = mover.cast_static(-Vec3::Y, 10.0, &ridge).unwrap();
+        assert!((cast.distance - (5.0 - SQRT_2 - 0.5)).abs() < 1.0e-3);
+        assert!(is_near(cast.normal, Vec3::Y));
+        assert!(is_near(cast.point,  Vec3::new(0.3, SQRT_2, 0.0)));
+
+        // Off to the side of the ridge we land on one of its faces
+        let mover: ShapeMoving = Ball::new(Vec3::new(0.0, 5.0, 0.5), 0.5).into();
+        let cast = mover.cast_static(-Vec3::Y, 10.0, &ridge).unwrap();
+        assert!(is_near(cast.normal, Vec3::new(0.0, 1.0, 1.0).normalize()));
+
+        // Turning the ramp about Y turns its slope to face +Z instead of -X
+        let ramp: ShapeStatic = Ramp::new(Vec3::ZERO, Vec3::ONE, Quat::from_rotation_y(FRAC_PI_2)).into();
+        let mover: ShapeMoving = Ball::new(Vec3::new(0.0, 5.0, 0.0), 0.5).into();
+        let cast = mover.cast_static(-Vec3::Y, 10.0, &ramp).unwrap();
+        assert!(is_near(cast.normal, Vec3::new(0.0, 1.0, 1.0).normalize()));
+        assert!((cast.distance - (5.0 - 0.5*SQRT_2)).abs() < 1.0e-3);
+
+        // Sliding along Z into a box turned about Y, we hit its leading edge
+        let target: ShapeStatic = BoxOriented::new(Vec3::ZERO, Vec3::ONE, Quat::from_rotation_y(FRAC_PI_4)).into();
+        let mover:  ShapeMoving = BoxAligned::new(Vec3::new(0.0, 0.0, 5.0), Vec3::splat(0.5)).into();
+        let cast = mover.cast_static(-Vec3::Z, 10.0, &target).unwrap();
+        assert!((cast.distance - (5.0 - SQRT_2 - 0.5)).abs() < 1.0e-3);
+        assert!(is_near(cast.point, Vec3::new(0.0, cast.point.y, SQRT_2)));
+    }
+
+    #[test]
+    fn check_cast_grazing() {
+        let ground: ShapeStatic = BoxAligned::new(Vec3::ZERO, Vec3::ONE).into();
+
+        // Passing just over the top edge misses
+        let mover: ShapeMoving = Ball::new(Vec3::new(-5.0, 1.51, 0.3), 0.5).into();
+        assert!(mover.cast_static(Vec3::X, 10.0, &ground).is_none());
+
+        // Dipping just under it clips the edge, and gets pushed up and back
+        let mover: ShapeMoving = Ball::new(Vec3::new(-5.0, 1.49, 0.3), 0.5).into();
+        let cast = mover.cast_static(Vec3::X, 10.0, &ground).unwrap();
+        assert!(cast.normal.x < 0.0 && cast.normal.y > 0.0 && cast.normal.z.abs() < 1.0e-3);
+        assert!(is_near(cast.point, Vec3::new(-1.0, 1.0, 0.3)));
+        assert!(((cast.origin - cast.point).length() - 0.5).abs() < 1.0e-3);
+
+        // Sweeping diagonally across the top face without ever dipping into it
+        let mover: ShapeMoving = BoxAligned::new(Vec3::new(-3.0, 1.51, -3.0), Vec3::splat(0.5)).into();
+        assert!(mover.cast_static(Vec3::new(1.0, 0.0, 1.0), 10.0, &ground).is_none());
+    }
+}
@@ -0,0 +1,92 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use bevy::math::Vec3;
+use macro_attr_2018::macro_attr;
+use enum_derive_2018::EnumFromInner;
+use tinyvec::ArrayVec;
+
+use crate::prelude3d::*;
+
+macro_attr! {
+    #[derive(EnumFromInner!, Debug, Copy, Clone)]
+    pub enum ShapeMoving {
+        Ball(Ball),
+        BoxAligned(BoxAligned),
+        BoxOriented(BoxOriented),
+        Capsule(Capsule),
+    }
+}
+
+impl ShapeMoving {
+
+    pub fn origin(&self) -> Vec3 {
+        match self {
+            ShapeMoving::Ball(s)        => s.origin,
+            ShapeMoving::BoxAligned(s)  => s.origin,
+            ShapeMoving::BoxOriented(s) => s.origin,
+            ShapeMoving::Capsule(s)     => s.origin,
+        }
+    }
+
+    pub(crate) fn get_convex_hull(&self) -> (ArrayVec<[Vec3; HULL_CAPACITY]>, f32) {
+        match self {
+            ShapeMoving::Ball(s)        => (ArrayVec::from_iter([s.origin]), s.radius),
+            ShapeMoving::BoxAligned(s)  => (s.get_points().into_iter().collect(), 0.0),
+            ShapeMoving::BoxOriented(s) => (s.get_points().into_iter().collect(), 0.0),
+            ShapeMoving::Capsule(s)     => (s.get_points().into_iter().collect(), s.radius),
+        }
+    }
+
+}
+
+// NOTE Every moving shape can also be hit by others, so it's a static shape too
+impl From<ShapeMoving> for ShapeStatic {
+    fn from(value: ShapeMoving) -> Self {
+        match value {
+            ShapeMoving::Ball(s)        => s.into(),
+            ShapeMoving::BoxAligned(s)  => s.into(),
+            ShapeMoving::BoxOriented(s) => s.into(),
+            ShapeMoving::Capsule(s)     => s.into(),
+        }
+    }
+}
+
+impl ShapeCommon for ShapeMoving {
+    fn bounding_box(&self) -> BoxAligned {
+        match self {
+            ShapeMoving::Ball(s)        => s.bounding_box(),
+            ShapeMoving::BoxAligned(s)  => s.bounding_box(),
+            ShapeMoving::BoxOriented(s) => s.bounding_box(),
+            ShapeMoving::Capsule(s)     => s.bounding_box(),
+        }
+    }
+
+    fn origin(&self) -> Vec3 {
+        match self {
+            ShapeMoving::Ball(s)        => s.origin(),
+            ShapeMoving::BoxAligned(s)  => s.origin(),
+            ShapeMoving::BoxOriented(s) => s.origin(),
+            ShapeMoving::Capsule(s)     => s.origin(),
+        }
+    }
+
+    fn set_origin(&mut self, origin: Vec3) {
+        match self {
+            ShapeMoving::Ball(s)        => s.set_origin(origin),
+            ShapeMoving::BoxAligned(s)  => s.set_origin(origin),
+            ShapeMoving::BoxOriented(s) => s.set_origin(origin),
+            ShapeMoving::Capsule(s)     => s.set_origin(origin),
+        }
+    }
+}
+
+impl RayTarget for ShapeMoving {
+    fn raycast(&self, ray: &RayCaster) -> Option<[RayIntersection; 2]> {
+        match self {
+            ShapeMoving::Ball(s)        => s.raycast(ray),
+            ShapeMoving::BoxAligned(s)  => s.raycast(ray),
+            ShapeMoving::BoxOriented(s) => s.raycast(ray),
+            ShapeMoving::Capsule(s)     => s.raycast(ray),
+        }
+    }
+}
@@ -0,0 +1,65 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use bevy::prelude::Vec3;
+
+use crate::prelude3d::{RayCaster, ShapeCombined, ShapeMoving, ShapeStatic};
+
+// NOTE Same as 2D, we overlap when our origin is strictly inside the combined
+//      shape, so resting contacts don't count. The hull distance can't tell
+//      touching from inside, so instead we cast through the shape towards a
+//      point inside it and check we're past where the ray enters.
+// TODO Depenetration, that needs the depth too, so EPA or similar.
+
+impl ShapeMoving {
+
+    pub fn overlaps_static(&self, other: &ShapeStatic) -> bool {
+        is_overlapping(&ShapeCombined::between_moving_and_static(self, other), self.origin())
+    }
+
+    pub fn overlaps_moving(&self, other: &ShapeMoving) -> bool {
+        is_overlapping(&ShapeCombined::between_moving(self, other), self.origin())
+    }
+
+}
+
+fn is_overlapping(combined: &ShapeCombined, origin: Vec3) -> bool {
+    const EPSILON: f32 = 1.0e-4;
+    let inside = [Vec3::X, Vec3::Y, Vec3::Z, -Vec3::X, -Vec3::Y, -Vec3::Z].map(|v| combined.find_support(v)).iter().sum::<Vec3>()/6.0;
+    RayCaster::new(origin, inside - origin).test(combined).is_some_and(|[enter, exit]| enter.distance < -EPSILON && exit.distance > EPSILON)
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::{Quat, Vec3};
+    use crate::prelude3d::{Ball, BoxAligned, BoxOriented, Capsule, Ramp, ShapeMoving, ShapeStatic};
+
+    #[test]
+    fn check_overlaps() {
+        let ground: ShapeStatic = BoxAligned::new(Vec3::ZERO, Vec3::new(2.0, 0.5, 2.0)).into();
+
+        let mover: ShapeMoving = BoxAligned::new(Vec3::new(0.0, 0.9, 0.0), Vec3::splat(0.5)).into();
+        assert!(mover.overlaps_static(&ground));
+
+        let mover: ShapeMoving = BoxAligned::new(Vec3::new(0.0, 1.0, 0.0), Vec3::splat(0.5)).into();
+        assert!(!mover.overlaps_static(&ground));
+
+        // Turned 45 degrees, the corner pokes down further than the face did
+        let mover: ShapeMoving = BoxOriented::new(Vec3::new(0.0, 1.1, 0.0), Vec3::splat(0.5), Quat::from_rotation_z(std::f32::consts::FRAC_PI_4)).into();
+        assert!(mover.overlaps_static(&ground));
+
+        // Rounded ends hanging off the edge of the ground
+        let mover: ShapeMoving = Capsule::new_vertical(Vec3::new(2.3, 1.3, 0.0), 2.0, 0.5).into();
+        assert!(mover.overlaps_static(&ground));
+        let mover: ShapeMoving = Capsule::new_vertical(Vec3::new(2.3, 1.5, 0.0), 2.0, 0.5).into();
+        assert!(!mover.overlaps_static(&ground));
+        assert!( mover.overlaps_moving(&Ball::new(Vec3::new(2.3, 2.9, 0.0), 0.45).into()));
+        assert!(!mover.overlaps_moving(&Ball::new(Vec3::new(2.3, 3.0, 0.0), 0.45).into()));
+
+        // Above the low end of the ramp, but inside where it rises
+        let ramp: ShapeStatic = Ramp::new(Vec3::ZERO, Vec3::ONE, Quat::IDENTITY).into();
+        let mover: ShapeMoving = Ball::new(Vec3::new(-0.5, 0.2, 0.0), 0.1).into();
+        assert!(!mover.overlaps_static(&ramp));
+        let mover: ShapeMoving = Ball::new(Vec3::new(0.5, 0.2, 0.0), 0.1).into();
+        assert!(mover.overlaps_static(&ramp));
+    }
+}
@@ -0,0 +1,95 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use bevy::math::Vec3;
+use macro_attr_2018::macro_attr;
+use enum_derive_2018::EnumFromInner;
+use tinyvec::ArrayVec;
+
+use crate::prelude3d::*;
+
+macro_attr! {
+    #[derive(EnumFromInner!, Debug, Copy, Clone)]
+    pub enum ShapeStatic {
+        Ball(Ball),
+        BoxAligned(BoxAligned),
+        BoxAlignedRound(BoxAlignedRound),
+        BoxOriented(BoxOriented),
+        BoxOrientedRound(BoxOrientedRound),
+        Ramp(Ramp),
+        RampRound(RampRound),
+        Capsule(Capsule),
+    }
+}
+
+impl ShapeStatic {
+
+    pub(crate) fn get_convex_hull(&self) -> (ArrayVec<[Vec3; HULL_CAPACITY]>, f32) {
+        match self {
+            ShapeStatic::Ball(s)             => (ArrayVec::from_iter([s.origin]), s.radius),
+            ShapeStatic::BoxAligned(s)       => (s.get_points().into_iter().collect(), 0.0),
+            ShapeStatic::BoxAlignedRound(s)  => (s.get_points().into_iter().collect(), s.radius),
+            ShapeStatic::BoxOriented(s)      => (s.get_points().into_iter().collect(), 0.0),
+            ShapeStatic::BoxOrientedRound(s) => (s.get_points().into_iter().collect(), s.radius),
+            ShapeStatic::Ramp(s)             => (s.get_points().into_iter().collect(), 0.0),
+            ShapeStatic::RampRound(s)        => (s.get_points().into_iter().collect(), s.radius),
+            ShapeStatic::Capsule(s)          => (s.get_points().into_iter().collect(), s.radius),
+        }
+    }
+
+}
+
+impl ShapeCommon for ShapeStatic {
+    fn bounding_box(&self) -> BoxAligned {
+        match self {
+            ShapeStatic::Ball(s)             => s.bounding_box(),
+            ShapeStatic::BoxAligned(s)       => s.bounding_box(),
+            ShapeStatic::BoxAlignedRound(s)  => s.bounding_box(),
+            ShapeStatic::BoxOriented(s)      => s.bounding_box(),
+            ShapeStatic::BoxOrientedRound(s) => s.bounding_box(),
+            ShapeStatic::Ramp(s)             => s.bounding_box(),
+            ShapeStatic::RampRound(s)        => s.bounding_box(),
+            ShapeStatic::Capsule(s)          => s.bounding_box(),
+        }
+    }
+
+    fn origin(&self) -> Vec3 {
+        match self {
+            ShapeStatic::Ball(s)             => s.origin(),
+            ShapeStatic::BoxAligned(s)       => s.origin(),
+            ShapeStatic::BoxAlignedRound(s)  => s.origin(),
+            ShapeStatic::BoxOriented(s)      => s.origin(),
+            ShapeStatic::BoxOrientedRound(s) => s.origin(),
+            ShapeStatic::Ramp(s)             => s.origin(),
+            ShapeStatic::RampRound(s)        => s.origin(),
+            ShapeStatic::Capsule(s)          => s.origin(),
+        }
+    }
+
+    fn set_origin(&mut self, origin: Vec3) {
+        match self {
+            ShapeStatic::Ball(s)             => s.set_origin(origin),
+            ShapeStatic::BoxAligned(s)       => s.set_origin(origin),
+            ShapeStatic::BoxAlignedRound(s)  => s.set_origin(origin),
+            ShapeStatic::BoxOriented(s)      => s.set_origin(origin),
+            ShapeStatic::BoxOrientedRound(s) => s.set_origin(origin),
+            ShapeStatic::Ramp(s)             => s.set_origin(origin),
+            ShapeStatic::RampRound(s)        => s.set_origin(origin),
+            ShapeStatic::Capsule(s)          => s.set_origin(origin),
+        }
+    }
+}
+
+impl RayTarget for ShapeStatic {
+    fn raycast(&self, ray: &RayCaster) -> Option<[RayIntersection; 2]> {
+        match self {
+            ShapeStatic::Ball(s)             => s.raycast(ray),
+            ShapeStatic::BoxAligned(s)       => s.raycast(ray),
+            ShapeStatic::BoxAlignedRound(s)  => s.raycast(ray),
+            ShapeStatic::BoxOriented(s)      => s.raycast(ray),
+            ShapeStatic::BoxOrientedRound(s) => s.raycast(ray),
+            ShapeStatic::Ramp(s)             => s.raycast(ray),
+            ShapeStatic::RampRound(s)        => s.raycast(ray),
+            ShapeStatic::Capsule(s)          => s.raycast(ray),
+        }
+    }
+}
@@ -0,0 +1,203 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use bevy::prelude::{Mat2, Mat3, Quat, Vec2, Vec3};
+use tinyvec::ArrayVec;
+
+use crate::prelude3d::BoxAligned;
+
+// NOTE Every shape is the convex hull of at most this many points, plus a radius.
+pub const HULL_CAPACITY: usize = 8;
+
+const HULL_EPSILON:        f32   = 1.0e-4;
+const HULL_GRAZE:          f32   = 1.0e-3;
+const GJK_TOLERANCE:       f32   = 1.0e-6;
+const GJK_MAX_ITERATIONS:  usize = 32;
+const CAST_MAX_ITERATIONS: usize = 64;
+
+pub(crate) fn get_points_for_box(origin: Vec3, size: Vec3, rotation: Quat) -> [Vec3; 8] {
+    [
+        Vec3::new( size.x,  size.y,  size.z),
+        Vec3::new(-size.x,  size.y,  size.z),
+        Vec3::new(-size.x, -size.y,  size.z),
+        Vec3::new( size.x, -size.y,  size.z),
+        Vec3::new( size.x,  size.y, -size.z),
+        Vec3::new(-size.x,  size.y, -size.z),
+        Vec3::new(-size.x, -size.y, -size.z),
+        Vec3::new( size.x, -size.y, -size.z),
+    ].map(|v| origin + rotation*v)
+}
+
+pub(crate) fn get_planes_for_box(origin: Vec3, size: Vec3, rotation: Quat) -> [(Vec3, f32); 6] {
+    let axes = [rotation*Vec3::X, rotation*Vec3::Y, rotation*Vec3::Z];
+    [
+        ( axes[0], size.x), (-axes[0], size.x),
+        ( axes[1], size.y), (-axes[1], size.y),
+        ( axes[2], size.z), (-axes[2], size.z),
+    ].map(|(normal, offset)| (normal, normal.dot(origin) + offset))
+}
+
+// NOTE Ramps are right angled wedges centred in their bounds. Without any
+//      rotation they rise towards +X, with the slope running across Z.
+pub(crate) fn get_points_for_ramp(origin: Vec3, size: Vec3, rotation: Quat) -> [Vec3; 6] {
+    [
+        Vec3::new(-size.x, -size.y,  size.z),
+        Vec3::new( size.x, -size.y,  size.z),
+        Vec3::new( size.x,  size.y,  size.z),
+        Vec3::new(-size.x, -size.y, -size.z),
+        Vec3::new( size.x, -size.y, -size.z),
+        Vec3::new( size.x,  size.y, -size.z),
+    ].map(|v| origin + rotation*v)
+}
+
+pub(crate) fn get_normal_for_ramp(size: Vec3, rotation: Quat) -> Vec3 {
+    rotation*Vec3::new(-size.y, size.x, 0.0).normalize()
+}
+
+pub(crate) fn get_planes_for_ramp(origin: Vec3, size: Vec3, rotation: Quat) -> [(Vec3, f32); 5] {
+    [
+        (rotation* Vec3::X, size.x),
+        (rotation*-Vec3::Y, size.y),
+        (rotation* Vec3::Z, size.z),
+        (rotation*-Vec3::Z, size.z),
+        (get_normal_for_ramp(size, rotation), 0.0),
+    ].map(|(normal, offset)| (normal, normal.dot(origin) + offset))
+}
+
+pub(crate) fn get_bounds_for_points(points: &[Vec3], radius: f32) -> BoxAligned {
+    let (min, max) = points.iter().fold((Vec3::MAX, Vec3::MIN), |(min, max), &v| (min.min(v), max.max(v)));
+    BoxAligned::new(0.5*(min + max), 0.5*(max - min) + Vec3::splat(radius))
+}
+
+pub(crate) fn find_support(points: &[Vec3], direction: Vec3) -> Vec3 {
+    points.iter().copied().fold((-f32::INFINITY, Vec3::ZERO), |(best, result), v| {
+        let dist = v.dot(direction);
+        if dist > best { (dist, v) } else { (best, result) }
+    }).1
+}
+
+// ///////// //
+// // GJK // //
+// ///////// //
+
+// NOTE Finds the closest point on a convex hull, given its support function.
+//      Points inside the hull are their own closest point, at zero distance.
+
+pub(crate) fn find_closest_on_hull(support: &impl Fn(Vec3) -> Vec3, point: Vec3) -> (Vec3, f32) {
+    let mut simplex: ArrayVec<[Vec3; 4]> = ArrayVec::new();
+    let mut closest = support(Vec3::X) - point;
+    simplex.push(closest);
+
+    for _ in 0..GJK_MAX_ITERATIONS {
+        let dist_sq = closest.length_squared();
+        if dist_sq <= HULL_EPSILON*HULL_EPSILON {
+            return (point, 0.0);
+        }
+
+        // Stop once the hull doesn't reach meaningfully closer than we are
+        let next = support(-closest) - point;
+        if dist_sq - closest.dot(next) <= GJK_TOLERANCE*dist_sq || simplex.contains(&next) {
+            break;
+        }
+
+        simplex.push(next);
+        closest = reduce_simplex(&mut simplex);
+        if simplex.len() == 4 {
+            return (point, 0.0);
+        }
+    }
+
+    (point + closest, closest.length())
+}
+
+// NOTE The simplex is at most 4 points, so we just try every face of it and
+//      keep the closest that actually contains its projection of the origin.
+fn reduce_simplex(simplex: &mut ArrayVec<[Vec3; 4]>) -> Vec3 {
+    let mut best = (f32::INFINITY, 0_u32, simplex[0]);
+    for mask in 1_u32..(1 << simplex.len()) {
+        let points = get_simplex_subset(simplex, mask);
+        let Some(closest) = find_closest_on_simplex(&points) else { continue };
+        let dist_sq = closest.length_squared();
+        if dist_sq < best.0 || (dist_sq == best.0 && mask.count_ones() < best.1.count_ones()) {
+            best = (dist_sq, mask, closest);
+        }
+    }
+
+    *simplex = get_simplex_subset(simplex, best.1);
+    best.2
+}
+
+fn get_simplex_subset(simplex: &[Vec3], mask: u32) -> ArrayVec<[Vec3; 4]> {
+    simplex.iter().enumerate().filter(|(i, _)| mask & (1 << i) != 0).map(|(_, &v)| v).collect()
+}
+
+// NOTE Only Some when the closest point of the simplex's span is inside it
+fn find_closest_on_simplex(points: &[Vec3]) -> Option<Vec3> {
+    const MIN_BARYCENTRIC: f32 = -1.0e-6;
+    let origin = points[0];
+    match points.len() {
+        1 => Some(origin),
+        2 => {
+            let edge = points[1] - origin;
+            let len_sq = edge.length_squared();
+            let t = -origin.dot(edge)/len_sq;
+            (len_sq > 0.0 && (MIN_BARYCENTRIC..=1.0 - MIN_BARYCENTRIC).contains(&t)).then(|| origin + edge*t)
+        },
+        3 => {
+            let edges = [points[1] - origin, points[2] - origin];
+            let gram  = Mat2::from_cols(
+                Vec2::new(edges[0].dot(edges[0]), edges[0].dot(edges[1])),
+                Vec2::new(edges[0].dot(edges[1]), edges[1].dot(edges[1])),
+            );
+            if gram.determinant().abs() <= GJK_TOLERANCE*gram.x_axis.x*gram.y_axis.y {
+                return None;
+            }
+
+            let t = gram.inverse()*Vec2::new(-origin.dot(edges[0]), -origin.dot(edges[1]));
+            (t.min_element() >= MIN_BARYCENTRIC && t.x + t.y <= 1.0 - MIN_BARYCENTRIC).then(|| origin + edges[0]*t.x + edges[1]*t.y)
+        },
+        _ => {
+            let edges = Mat3::from_cols(points[1] - origin, points[2] - origin, points[3] - origin);
+            let scale = edges.x_axis.length()*edges.y_axis.length()*edges.z_axis.length();
+            if edges.determinant().abs() <= GJK_TOLERANCE*scale {
+                return None;
+            }
+
+            let t = edges.inverse()*-origin;
+            (t.min_element() >= MIN_BARYCENTRIC && t.x + t.y + t.z <= 1.0 - MIN_BARYCENTRIC).then_some(Vec3::ZERO)
+        },
+    }
+}
+
+// NOTE Conservative advancement, we step along the ray by how far we are from
+//      the hull over how quickly we're closing on it. That can't step through
+//      the hull, so once we're moving away from the closest point we've missed.
+
+pub(crate) fn cast_hull(support: &impl Fn(Vec3) -> Vec3, radius: f32, origin: Vec3, direction: Vec3, max_dist: f32) -> Option<(f32, Vec3)> {
+    let mut distance = 0.0;
+    let mut normal   = -direction;
+    for _ in 0..CAST_MAX_ITERATIONS {
+        let point = origin + direction*distance;
+        let (closest, gap) = find_closest_on_hull(support, point);
+        // Once we're this close the hull distance isn't precise enough to tell
+        // grazing the hull from just missing it, so call it a hit.
+        let closing = -direction.dot((point - closest)/gap);
+        if gap <= radius + HULL_EPSILON || (closing <= 0.0 && gap <= radius + HULL_GRAZE) {
+            // Without a radius we're too close for a good normal, the last step's will do
+            if radius > HULL_EPSILON && gap > HULL_EPSILON {
+                normal = (point - closest)/gap;
+            }
+            return Some((distance, normal));
+        }
+
+        normal = (point - closest)/gap;
+        if closing <= 0.0 {
+            return None;
+        }
+
+        distance += (gap - radius)/closing;
+        if distance > max_dist {
+            return None;
+        }
+    }
+    None
+}
@@ -0,0 +1,31 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+// NOTE Only for tests and benches. Other crates get it with the test-util
+//      feature, our own benches pull this file in by path. Each only uses part of it.
+#![allow(dead_code, clippy::should_implement_trait)]
+
+use bevy::prelude::Vec2;
+
+// NOTE Cheap xorshift so we don't need a dependency for fuzzing
+pub struct Rng(pub u32);
+
+impl Rng {
+    pub fn next(&mut self) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 <<  5;
+        self.0
+    }
+
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min)*((self.next() >> 8) as f32 / (1 << 24) as f32)
+    }
+
+    pub fn point(&mut self, extent: f32) -> Vec2 {
+        Vec2::new(self.range(-extent, extent), self.range(-extent, extent))
+    }
+
+    pub fn direction(&mut self) -> Vec2 {
+        Vec2::from_angle(self.range(0.0, std::f32::consts::TAU))
+    }
+}
@@ -0,0 +1,182 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use std::time::{Duration, Instant};
+
+use bevy::prelude::Vec2;
+use nvm_collide::{prelude::*, test_util::Rng};
+use nvm_platformer::{Buffer, CollisionBroadphase, CollisionCandidate, CollisionFilter, CollisionGrid, CollisionTree};
+use tinyvec::SliceVec;
+
+const SHAPE_COUNTS: [usize; 3] = [1_000, 5_000, 10_000];
+const QUERY_COUNT:  usize      = 1_000;
+const ROUNDS:       usize      = 10;
+
+// NOTE The world grows with the shape count, so each query sees about the
+//      same number of neighbours and we're only measuring how well it scales.
+
+pub fn main() {
+    println!("{:>8} {:>10} {:>12} {:>12} {:>12}", "shapes", "broadphase", "build", "update", "query");
+    for count in SHAPE_COUNTS {
+        let extent = 4.0*(count as f32).sqrt();
+        let shapes = create_shapes(count, extent);
+        let movers = create_movers(QUERY_COUNT, extent);
+
+        let brute = run::<CandidateList>(&shapes, &movers, CandidateList::default);
+        let grid  = run::<CollisionGrid>(&shapes, &movers, CollisionGrid::default);
+        let tree  = run::<CollisionTree>(&shapes, &movers, CollisionTree::default);
+
+        for (name, result) in [("brute", brute), ("grid", grid), ("tree", tree)] {
+            println!(
+                "{:>8} {:>10} {:>12.3?} {:>12.3?} {:>12.3?}",
+                count, name, result.build, result.update, result.query
+            );
+        }
+
+        assert_eq!(brute.found, grid.found, "grid disagrees with brute force");
+        assert_eq!(brute.found, tree.found, "tree disagrees with brute force");
+    }
+}
+
+#[derive(Clone, Copy)]
+struct BenchResult {
+    build:  Duration,
+    update: Duration,
+    query:  Duration,
+    found:  usize,
+}
+
+trait BenchBroadphase: CollisionBroadphase {
+    type Id: Copy;
+    fn insert(&mut self, item: CollisionCandidate) -> Self::Id;
+    fn update(&mut self, id: Self::Id, collider: ShapeStatic);
+}
+
+fn run<T: BenchBroadphase>(shapes: &[ShapeStatic], movers: &[(ShapeMoving, Vec2)], create: impl Fn() -> T) -> BenchResult {
+    let mut buffer = Buffer::<CollisionCandidate>::new(shapes.len());
+    let mut result = BenchResult{
+        build:  Duration::ZERO,
+        update: Duration::ZERO,
+        query:  Duration::ZERO,
+        found:  0,
+    };
+
+    for _ in 0..ROUNDS {
+        let start = Instant::now();
+        let mut broadphase = create();
+        let ids: Vec<_> = shapes.iter().map(|collider| broadphase.insert(CollisionCandidate{ collider: collider.clone(), ..Default::default() })).collect();
+        result.build += start.elapsed();
+
+        // Everything drifts a little, like it would over a frame
+        let start = Instant::now();
+        for (&id, collider) in ids.iter().zip(shapes) {
+            let mut collider = collider.clone();
+            collider.set_origin(collider.origin() + Vec2::new(0.05, -0.05));
+            broadphase.update(id, collider);
+        }
+        result.update += start.elapsed();
+
+        let start = Instant::now();
+        result.found = 0;
+        for (mover, direction) in movers {
+            let mut out = buffer.get();
+            result.found += broadphase.find_candidates(mover, *direction, 2.0, &CollisionFilter::ALL, &mut out);
+        }
+        result.query += start.elapsed();
+    }
+
+    result.build  /= ROUNDS as u32;
+    result.update /= ROUNDS as u32;
+    result.query  /= ROUNDS as u32;
+    result
+}
+
+// ///////////////// //
+// // Broadphases // //
+// ///////////////// //
+
+#[derive(Default)]
+struct CandidateList(Vec<CollisionCandidate>);
+
+impl CollisionBroadphase for CandidateList {
+    fn find_candidates(&self, collider: &ShapeMoving, direction: Vec2, max_dist: f32, filter: &CollisionFilter, out: &mut SliceVec<CollisionCandidate>) -> usize {
+        let offset = direction * max_dist;
+        let [min, max] = collider.bounding_box().bounds();
+        let [min, max] = [min.min(min + offset), max.max(max + offset)];
+
+        let size = out.len();
+        for candidate in self.0.iter() {
+            let [c_min, c_max] = candidate.collider.bounding_box().bounds();
+            let overlaps = c_min.cmple(max).all() && min.cmple(c_max).all();
+            if !overlaps || !filter.matches(candidate) {
+                continue;
+            }
+
+            if out.len() == out.capacity() {
+                break;
+            }
+            out.push(candidate.clone());
+        }
+        out.len() - size
+    }
+}
+
+impl BenchBroadphase for CandidateList {
+    type Id = usize;
+
+    fn insert(&mut self, item: CollisionCandidate) -> Self::Id {
+        self.0.push(item);
+        self.0.len() - 1
+    }
+
+    fn update(&mut self, id: Self::Id, collider: ShapeStatic) {
+        self.0[id].collider = collider;
+    }
+}
+
+impl BenchBroadphase for CollisionGrid {
+    type Id = nvm_platformer::CollisionGridId;
+
+    fn insert(&mut self, item: CollisionCandidate) -> Self::Id {
+        CollisionGrid::insert(self, item)
+    }
+
+    fn update(&mut self, id: Self::Id, collider: ShapeStatic) {
+        CollisionGrid::update(self, id, collider);
+    }
+}
+
+impl BenchBroadphase for CollisionTree {
+    type Id = nvm_platformer::CollisionTreeId;
+
+    fn insert(&mut self, item: CollisionCandidate) -> Self::Id {
+        CollisionTree::insert(self, item)
+    }
+
+    fn update(&mut self, id: Self::Id, collider: ShapeStatic) {
+        CollisionTree::update(self, id, collider);
+    }
+}
+
+// /////////////// //
+// // Test Data // //
+// /////////////// //
+
+fn create_shapes(count: usize, extent: f32) -> Vec<ShapeStatic> {
+    let mut rng = Rng(0x2545_f491);
+    (0..count).map(|i| {
+        let origin = rng.point(extent);
+        match i % 3 {
+            0 => BoxAligned::new(origin, Vec2::new(rng.range(0.2, 1.0), rng.range(0.2, 1.0))).into(),
+            1 => Ball::new(origin, rng.range(0.2, 0.8)).into(),
+            _ => BoxOriented::new(origin, Vec2::new(rng.range(0.2, 1.0), rng.range(0.2, 1.0)), rng.direction()).into(),
+        }
+    }).collect()
+}
+
+fn create_movers(count: usize, extent: f32) -> Vec<(ShapeMoving, Vec2)> {
+    let mut rng = Rng(0x9e37_79b9);
+    (0..count).map(|_| {
+        let mover: ShapeMoving = BoxAligned::new(rng.point(extent), Vec2::new(0.4, 0.9)).into();
+        (mover, rng.direction())
+    }).collect()
+}
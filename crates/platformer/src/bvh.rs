@@ -0,0 +1,459 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use bevy::{math::Vec2, ecs::system::Resource};
+use nvm_collide::prelude::*;
+use tinyvec::SliceVec;
+
+use crate::{CollisionBroadphase, CollisionCandidate, CollisionFilter};
+
+const NULL_NODE: usize = usize::MAX;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CollisionTreeId(usize, u32);
+
+#[derive(Debug, Clone)]
+struct TreeNode {
+    bounds:   [Vec2; 2],
+    parent:   usize,
+    children: [usize; 2],
+    height:   i32,
+    version:  u32,
+    item:     CollisionCandidate,
+}
+
+impl TreeNode {
+    fn is_leaf(&self) -> bool {
+        self.children[0] == NULL_NODE
+    }
+}
+
+// NOTE This is a dynamic AABB tree in the style of Box2D. Leaves store fattened
+//      bounds, so shapes can move a little without the tree being touched.
+//      Free nodes are linked through their parent and have a negative height.
+//      Nodes get reused, so ids also hold a version that's bumped on every
+//      free, otherwise an old id could reach whatever took its place.
+
+#[derive(Resource)]
+pub struct CollisionTree {
+    nodes:  Vec<TreeNode>,
+    root:   usize,
+    free:   usize,
+    count:  usize,
+    margin: f32,
+}
+
+impl Default for CollisionTree {
+    fn default() -> Self {
+        Self::new(0.1)
+    }
+}
+
+impl CollisionTree {
+
+    pub fn new(margin: f32) -> Self {
+        Self{
+            nodes:  Vec::new(),
+            root:   NULL_NODE,
+            free:   NULL_NODE,
+            count:  0,
+            margin,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub fn get(&self, id: CollisionTreeId) -> Option<&CollisionCandidate> {
+        self.is_valid_leaf(id).then(|| &self.nodes[id.0].item)
+    }
+
+    pub fn insert(&mut self, item: CollisionCandidate) -> CollisionTreeId {
+        let leaf = self.allocate_node();
+        self.nodes[leaf].bounds = self.fatten(item.collider.bounding_box().bounds());
+        self.nodes[leaf].height = 0;
+        self.nodes[leaf].item   = item;
+        self.insert_leaf(leaf);
+        self.count += 1;
+        CollisionTreeId(leaf, self.nodes[leaf].version)
+    }
+
+    pub fn remove(&mut self, id: CollisionTreeId) -> Option<CollisionCandidate> {
+        if !self.is_valid_leaf(id) {
+            return None;
+        }
+
+        let item = std::mem::take(&mut self.nodes[id.0].item);
+        self.remove_leaf(id.0);
+        self.free_node(id.0);
+        self.count -= 1;
+        Some(item)
+    }
+
+    // NOTE Returns true if the leaf had to be moved in the tree
+    pub fn update(&mut self, id: CollisionTreeId, collider: ShapeStatic) -> bool {
+        if !self.is_valid_leaf(id) {
+            return false;
+        }
+
+        let bounds = collider.bounding_box().bounds();
+        self.nodes[id.0].item.collider = collider;
+        if is_bounds_inside(bounds, self.nodes[id.0].bounds) {
+            return false;
+        }
+
+        self.remove_leaf(id.0);
+        self.nodes[id.0].bounds = self.fatten(bounds);
+        self.insert_leaf(id.0);
+        true
+    }
+
+    pub fn query_region(&self, region: &BoxAligned, filter: &CollisionFilter, out: &mut SliceVec<CollisionCandidate>) -> usize {
+        let region = region.bounds();
+        self.query(
+            |bounds| is_bounds_overlapping(*bounds, region),
+            filter,
+            out
+        )
+    }
+
+    pub fn query_ray(&self, ray: &RayCaster, filter: &CollisionFilter, out: &mut SliceVec<CollisionCandidate>) -> usize {
+        self.query(
+            |[min, max]| ray.test_rect(0.5*(*min + *max), 0.5*(*max - *min)).is_some_and(|[enter, exit]|
+                enter.distance <= ray.max_distance() && exit.distance >= ray.min_distance()
+            ),
+            filter,
+            out
+        )
+    }
+
+    fn query(&self, test: impl Fn(&[Vec2; 2]) -> bool, filter: &CollisionFilter, out: &mut SliceVec<CollisionCandidate>) -> usize {
+        let size = out.len();
+        if self.root == NULL_NODE {
+            return 0;
+        }
+
+        // OPT reuse the stack between queries
+        let mut stack = vec![self.root];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !test(&node.bounds) {
+                continue;
+            }
+
+            if !node.is_leaf() {
+                stack.extend(node.children);
+            } else if filter.matches(&node.item) && test(&node.item.collider.bounding_box().bounds()) {
+                if out.len() == out.capacity() {
+                    break;
+                }
+                out.push(node.item.clone());
+            }
+        }
+
+        out.len() - size
+    }
+
+    fn fatten(&self, [min, max]: [Vec2; 2]) -> [Vec2; 2] {
+        [min - Vec2::splat(self.margin), max + Vec2::splat(self.margin)]
+    }
+
+    fn is_valid_leaf(&self, CollisionTreeId(index, version): CollisionTreeId) -> bool {
+        self.nodes.get(index).is_some_and(|v| v.height == 0 && v.version == version)
+    }
+
+}
+
+// /////////////////////// //
+// // Tree Construction // //
+// /////////////////////// //
+
+impl CollisionTree {
+
+    fn allocate_node(&mut self) -> usize {
+        let node = TreeNode{
+            bounds:   [Vec2::ZERO; 2],
+            parent:   NULL_NODE,
+            children: [NULL_NODE; 2],
+            height:   0,
+            version:  0,
+            item:     CollisionCandidate::default(),
+        };
+
+        if self.free == NULL_NODE {
+            self.nodes.push(node);
+            self.nodes.len() - 1
+        } else {
+            let index = self.free;
+            self.free = self.nodes[index].parent;
+            self.nodes[index] = TreeNode{ version: self.nodes[index].version, ..node };
+            index
+        }
+    }
+
+    fn free_node(&mut self, index: usize) {
+        self.nodes[index].parent  = self.free;
+        self.nodes[index].height  = -1;
+        self.nodes[index].version = self.nodes[index].version.wrapping_add(1);
+        self.free = index;
+    }
+
+    fn insert_leaf(&mut self, leaf: usize) {
+        if self.root == NULL_NODE {
+            self.root = leaf;
+            self.nodes[leaf].parent = NULL_NODE;
+            return;
+        }
+
+        // Walk down the tree, picking whichever side grows the least
+        let leaf_bounds = self.nodes[leaf].bounds;
+        let mut index = self.root;
+        while !self.nodes[index].is_leaf() {
+            let node     = &self.nodes[index];
+            let area     = get_perimeter(node.bounds);
+            let combined = get_perimeter(get_union(node.bounds, leaf_bounds));
+
+            // Cost of making a new parent here, and of pushing the leaf further down
+            let cost     = 2.0*combined;
+            let inherit  = 2.0*(combined - area);
+            let [cost_0, cost_1] = node.children.map(|child| {
+                let child = &self.nodes[child];
+                let grown = get_perimeter(get_union(child.bounds, leaf_bounds));
+                if child.is_leaf() { grown + inherit } else { grown - get_perimeter(child.bounds) + inherit }
+            });
+
+            if cost < cost_0 && cost < cost_1 {
+                break;
+            }
+
+            index = if cost_0 < cost_1 { node.children[0] } else { node.children[1] };
+        }
+
+        // Replace the sibling with a new parent holding both
+        let sibling    = index;
+        let old_parent = self.nodes[sibling].parent;
+        let new_parent = self.allocate_node();
+        self.nodes[new_parent].parent   = old_parent;
+        self.nodes[new_parent].bounds   = get_union(leaf_bounds, self.nodes[sibling].bounds);
+        self.nodes[new_parent].height   = self.nodes[sibling].height + 1;
+        self.nodes[new_parent].children = [sibling, leaf];
+        self.nodes[sibling].parent = new_parent;
+        self.nodes[leaf].parent    = new_parent;
+        self.replace_child(old_parent, sibling, new_parent);
+
+        self.refit_from(new_parent);
+    }
+
+    fn remove_leaf(&mut self, leaf: usize) {
+        if leaf == self.root {
+            self.root = NULL_NODE;
+            return;
+        }
+
+        // Our sibling takes the place of our parent
+        let parent  = self.nodes[leaf].parent;
+        let grand   = self.nodes[parent].parent;
+        let [a, b]  = self.nodes[parent].children;
+        let sibling = if a == leaf { b } else { a };
+
+        self.replace_child(grand, parent, sibling);
+        self.nodes[sibling].parent = grand;
+        self.free_node(parent);
+
+        if grand != NULL_NODE {
+            self.refit_from(grand);
+        }
+    }
+
+    fn replace_child(&mut self, parent: usize, old: usize, new: usize) {
+        if parent == NULL_NODE {
+            self.root = new;
+        } else if self.nodes[parent].children[0] == old {
+            self.nodes[parent].children[0] = new;
+        } else {
+            self.nodes[parent].children[1] = new;
+        }
+    }
+
+    fn refit_from(&mut self, mut index: usize) {
+        while index != NULL_NODE {
+            index = self.balance(index);
+            self.refit(index);
+            index = self.nodes[index].parent;
+        }
+    }
+
+    fn refit(&mut self, index: usize) {
+        let [a, b] = self.nodes[index].children;
+        self.nodes[index].height = 1 + self.nodes[a].height.max(self.nodes[b].height);
+        self.nodes[index].bounds = get_union(self.nodes[a].bounds, self.nodes[b].bounds);
+    }
+
+    // NOTE If one side is more than one level taller than the other, we rotate
+    //      it up to take our place. Returns whichever node is now in our place.
+    fn balance(&mut self, index: usize) -> usize {
+        let node = &self.nodes[index];
+        if node.is_leaf() || node.height < 2 {
+            return index;
+        }
+
+        let [a, b] = node.children;
+        let diff = self.nodes[b].height - self.nodes[a].height;
+        if diff > 1 {
+            self.rotate_up(index, 1)
+        } else if diff < -1 {
+            self.rotate_up(index, 0)
+        } else {
+            index
+        }
+    }
+
+    fn rotate_up(&mut self, index: usize, side: usize) -> usize {
+        let child = self.nodes[index].children[side];
+        let other = self.nodes[index].children[1 - side];
+        let [c_a, c_b] = self.nodes[child].children;
+        let (taller, shorter) = if self.nodes[c_a].height > self.nodes[c_b].height { (c_a, c_b) } else { (c_b, c_a) };
+
+        // The child takes our place, we become its child along with its taller child
+        let parent = self.nodes[index].parent;
+        self.nodes[child].parent = parent;
+        self.replace_child(parent, index, child);
+        self.nodes[child].children = [index, taller];
+        self.nodes[index].parent   = child;
+
+        // We keep the other side, and take the shorter grandchild in place of the child
+        self.nodes[index].children = [other, shorter];
+        self.nodes[shorter].parent = index;
+
+        self.refit(index);
+        self.refit(child);
+        child
+    }
+
+}
+
+impl CollisionBroadphase for CollisionTree {
+    fn find_candidates(&self, collider: &ShapeMoving, direction: Vec2, max_dist: f32, filter: &CollisionFilter, out: &mut SliceVec<CollisionCandidate>) -> usize {
+        let offset = direction * max_dist;
+        let [min, max] = collider.bounding_box().bounds();
+        let [min, max] = [min.min(min + offset), max.max(max + offset)];
+        self.query_region(&BoxAligned::new(0.5*(min + max), 0.5*(max - min)), filter, out)
+    }
+
+    fn find_candidates_ray(&self, ray: &RayCaster, filter: &CollisionFilter, out: &mut SliceVec<CollisionCandidate>) -> usize {
+        self.query_ray(ray, filter, out)
+    }
+}
+
+fn get_union([a_min, a_max]: [Vec2; 2], [b_min, b_max]: [Vec2; 2]) -> [Vec2; 2] {
+    [a_min.min(b_min), a_max.max(b_max)]
+}
+
+fn get_perimeter([min, max]: [Vec2; 2]) -> f32 {
+    let size = max - min;
+    2.0*(size.x + size.y)
+}
+
+fn is_bounds_inside([inner_min, inner_max]: [Vec2; 2], [outer_min, outer_max]: [Vec2; 2]) -> bool {
+    inner_min.cmpge(outer_min).all() && inner_max.cmple(outer_max).all()
+}
+
+pub(crate) fn is_bounds_overlapping([a_min, a_max]: [Vec2; 2], [b_min, b_max]: [Vec2; 2]) -> bool {
+    a_min.cmple(b_max).all() && b_min.cmple(a_max).all()
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::{ecs::entity::Entity, math::Vec2};
+    use nvm_collide::{prelude::*, test_util::Rng};
+    use tinyvec::SliceVec;
+
+    use crate::{CollisionCandidate, CollisionFilter, COLLISION_LAYERS_ALL};
+    use super::{CollisionTree, CollisionTreeId};
+
+    fn get_candidate(rng: &mut Rng, entity: u32) -> CollisionCandidate {
+        CollisionCandidate{
+            entity:   Some(Entity::from_raw(entity)),
+            collider: BoxAligned::new(rng.point(10.0), Vec2::new(rng.range(0.1, 1.0), rng.range(0.1, 1.0))).into(),
+            layers:   1 << (rng.next() % 2),
+            ..Default::default()
+        }
+    }
+
+    fn get_entities<'a>(items: impl Iterator<Item = &'a CollisionCandidate>) -> Vec<Entity> {
+        let mut entities: Vec<Entity> = items.filter_map(|v| v.entity).collect();
+        entities.sort();
+        entities
+    }
+
+    #[test]
+    fn check_against_brute_force() {
+        let mut rng    = Rng(0x2545_f491);
+        let mut tree   = CollisionTree::new(0.1);
+        let mut items  = Vec::<(CollisionTreeId, CollisionCandidate)>::new();
+        let mut buffer = vec![CollisionCandidate::default(); 256];
+        for i in 0..2000 {
+            match rng.next() % 4 {
+                0 | 1 => {
+                    let item = get_candidate(&mut rng, i);
+                    items.push((tree.insert(item.clone()), item));
+                },
+                2 if !items.is_empty() => {
+                    let (id, item) = items.swap_remove(rng.next() as usize % items.len());
+                    assert_eq!(tree.remove(id).and_then(|v| v.entity), item.entity);
+                    assert!(tree.get(id).is_none());
+                },
+                _ if !items.is_empty() => {
+                    // Mostly small moves that stay inside the fattened bounds
+                    let idx    = rng.next() as usize % items.len();
+                    let extent = match rng.next() % 4 { 0 => 5.0, _ => 0.05 };
+                    let origin = items[idx].1.collider.origin() + rng.point(extent);
+                    items[idx].1.collider.set_origin(origin);
+                    tree.update(items[idx].0, items[idx].1.collider.clone());
+                },
+                _ => {},
+            }
+            assert_eq!(tree.len(), items.len());
+
+            let filter = CollisionFilter::new(COLLISION_LAYERS_ALL, 1 + rng.next() % 3);
+            let region = BoxAligned::new(rng.point(10.0), Vec2::new(rng.range(0.5, 3.0), rng.range(0.5, 3.0)));
+            let mut out = SliceVec::from_slice_len(&mut buffer, 0);
+            let count = tree.query_region(&region, &filter, &mut out);
+            assert!(count < out.capacity());
+            let expected = items.iter()
+                .map(|(_, v)| v)
+                .filter(|v| filter.matches(v) && super::is_bounds_overlapping(v.collider.bounding_box().bounds(), region.bounds()));
+            assert_eq!(get_entities(out.iter()), get_entities(expected));
+
+            let ray = RayCaster::new_segment(rng.point(10.0), rng.direction(), 0.0, rng.range(0.0, 10.0));
+            let mut out = SliceVec::from_slice_len(&mut buffer, 0);
+            let count = tree.query_ray(&ray, &filter, &mut out);
+            assert!(count < out.capacity());
+            let expected = items.iter()
+                .map(|(_, v)| v)
+                .filter(|v| filter.matches(v) && ray.test(&v.collider.bounding_box()).is_some());
+            assert_eq!(get_entities(out.iter()), get_entities(expected));
+        }
+    }
+
+    #[test]
+    fn check_stale_id() {
+        let mut rng  = Rng(0x9e37_79b9);
+        let mut tree = CollisionTree::new(0.1);
+        tree.insert(get_candidate(&mut rng, 0));
+        let a = tree.insert(get_candidate(&mut rng, 1));
+        tree.remove(a);
+
+        // The new leaf reuses the old one's node, but the old id can't reach it
+        let b = tree.insert(get_candidate(&mut rng, 2));
+        assert_eq!(a.0, b.0);
+        assert!(tree.get(a).is_none());
+        assert!(tree.remove(a).is_none());
+        assert!(!tree.update(a, BoxAligned::new(Vec2::splat(100.0), Vec2::ONE).into()));
+        assert_eq!(tree.get(b).and_then(|v| v.entity), Some(Entity::from_raw(2)));
+    }
+}
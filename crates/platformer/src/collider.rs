@@ -0,0 +1,315 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use bevy::{prelude::*, ecs::system::SystemParam, math::Affine2, transform::TransformSystem, utils::HashMap};
+use nvm_collide::prelude::*;
+use tinyvec::SliceVec;
+
+use crate::{
+    raycast_with, test_movement_with, trigger_update,
+    CollisionBroadphase, CollisionCandidate, CollisionFilter, CollisionTree, CollisionTreeId, CollisionWorld,
+    RayHit, RayQueryMode, SensorHit, TriggerContacts, TriggerEvent, COLLISION_LAYERS_ALL,
+};
+
+#[derive(Debug, Clone)]
+pub enum ColliderShape {
+    Static(ShapeStatic),
+    Moving(ShapeMoving),
+}
+
+impl ColliderShape {
+
+    pub fn to_static(&self) -> ShapeStatic {
+        match self {
+            ColliderShape::Static(s) => s.clone(),
+            ColliderShape::Moving(s) => (*s).into(),
+        }
+    }
+
+    pub fn to_moving(&self) -> Option<ShapeMoving> {
+        match self {
+            ColliderShape::Static(_) => None,
+            ColliderShape::Moving(s) => Some(*s),
+        }
+    }
+
+}
+
+// NOTE The shape is relative to the entity, so it follows the entity's full
+//      transform. Round shapes can't be stretched, so if the entity's scale
+//      isn't uniform only their origin follows it, and they keep their size.
+
+#[derive(Debug, Component, Clone)]
+pub struct Collider {
+    pub shape:   ColliderShape,
+    pub one_way: Option<Vec2>,
+    pub layers:  u32,
+    pub mask:    u32,
+}
+
+impl Collider {
+
+    pub fn new_static(shape: impl Into<ShapeStatic>) -> Self {
+        Self::new(ColliderShape::Static(shape.into()))
+    }
+
+    pub fn new_moving(shape: impl Into<ShapeMoving>) -> Self {
+        Self::new(ColliderShape::Moving(shape.into()))
+    }
+
+    pub fn new(shape: ColliderShape) -> Self {
+        Self{
+            shape,
+            one_way: None,
+            layers:  COLLISION_LAYERS_ALL,
+            mask:    COLLISION_LAYERS_ALL,
+        }
+    }
+
+    pub fn with_layers(self, layers: u32, mask: u32) -> Self {
+        Self{ layers, mask, ..self }
+    }
+
+    pub fn with_one_way(self, normal: Vec2) -> Self {
+        Self{ one_way: Some(normal), ..self }
+    }
+
+    pub fn get_world_shape(&self, transform: &GlobalTransform) -> ShapeStatic {
+        let shape  = self.shape.to_static();
+        let affine = get_transform_2d(transform);
+        shape.transformed(&affine).unwrap_or_else(|error| {
+            warn_transform_fallback(error);
+            let mut shape = shape;
+            shape.set_origin(affine.transform_point2(shape.origin()));
+            shape
+        })
+    }
+
+    pub fn get_candidate(&self, entity: Entity, transform: &GlobalTransform) -> CollisionCandidate {
+        CollisionCandidate{
+            entity:   Some(entity),
+            collider: self.get_world_shape(transform),
+            one_way:  self.one_way,
+            layers:   self.layers,
+            mask:     self.mask,
+        }
+    }
+
+}
+
+// NOTE Drops Z, along with any rotation that isn't around Z.
+//      Shapes that can't follow the result (ie. round shapes under a scale
+//      that isn't uniform) fall back to only moving their origin through it,
+//      so they keep their size and rotation. That's warned about once.
+pub fn get_transform_2d(transform: &GlobalTransform) -> Affine2 {
+    let affine = transform.affine();
+    Affine2::from_mat2_translation(
+        Mat2::from_cols(affine.matrix3.x_axis.truncate(), affine.matrix3.y_axis.truncate()),
+        affine.translation.truncate(),
+    )
+}
+
+pub(crate) fn warn_transform_fallback(error: &'static str) {
+    static WARNED: AtomicBool = AtomicBool::new(false);
+    if !WARNED.swap(true, Ordering::Relaxed) {
+        warn!("Collider shape can't follow its transform, only moving its origin: {error}");
+    }
+}
+
+// //////////// //
+// // Plugin // //
+// //////////// //
+
+#[derive(SystemSet, Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum CollideUpdate {
+    SyncColliders,
+    UpdateTriggers,
+}
+
+// NOTE Colliders are synced after transforms propagate, so anything that runs
+//      in Update sees where everything was at the end of the last frame.
+//      Triggers run after that, so their events are read in the next frame.
+
+pub struct CollidePlugin;
+
+impl Plugin for CollidePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<CollisionTree>()
+            .init_resource::<ColliderIndex>()
+            .init_resource::<TriggerContacts>()
+            .add_event::<TriggerEvent>()
+            .configure_sets(PostUpdate, (
+                CollideUpdate::SyncColliders,
+                CollideUpdate::UpdateTriggers,
+            ).chain().after(TransformSystem::TransformPropagate))
+            .add_systems(PostUpdate, (collider_remove, collider_sync).chain().in_set(CollideUpdate::SyncColliders))
+            .add_systems(PostUpdate, (trigger_update).in_set(CollideUpdate::UpdateTriggers));
+    }
+}
+
+#[derive(Resource, Default)]
+struct ColliderIndex(HashMap<Entity, CollisionTreeId>);
+
+fn collider_remove(
+    mut r_tree:    ResMut<CollisionTree>,
+    mut r_index:   ResMut<ColliderIndex>,
+    mut r_removed: RemovedComponents<Collider>,
+) {
+    for entity in r_removed.read() {
+        if let Some(id) = r_index.0.remove(&entity) {
+            r_tree.remove(id);
+        }
+    }
+}
+
+type ChangedColliders<'w, 's> = Query<'w, 's, (Entity, Ref<'static, Collider>, &'static GlobalTransform), Or<(Changed<Collider>, Changed<GlobalTransform>)>>;
+
+fn collider_sync(
+    q_colliders: ChangedColliders,
+    mut r_tree:  ResMut<CollisionTree>,
+    mut r_index: ResMut<ColliderIndex>,
+) {
+    for (entity, collider, transform) in q_colliders.iter() {
+        let candidate = collider.get_candidate(entity, transform);
+        match r_index.0.get(&entity).copied() {
+            // Only moved, the tree can often skip this
+            Some(id) if !collider.is_changed() => {
+                r_tree.update(id, candidate.collider);
+            },
+            // Layers and such might've changed too, so start over
+            Some(id) => {
+                r_tree.remove(id);
+                r_index.0.insert(entity, r_tree.insert(candidate));
+            },
+            None => {
+                r_index.0.insert(entity, r_tree.insert(candidate));
+            },
+        }
+    }
+}
+
+// /////////////////// //
+// // Spatial Query // //
+// /////////////////// //
+
+pub const SPATIAL_QUERY_CAPACITY: usize = 64;
+
+// NOTE Queries everything in the collision world, the buffers are kept between
+//      runs. Each query only looks at the first SPATIAL_QUERY_CAPACITY candidates,
+//      and warns the first time one has to drop the rest.
+
+#[derive(SystemParam)]
+pub struct SpatialQuery<'w, 's> {
+    world:      CollisionWorld<'w>,
+    candidates: Local<'s, Vec<CollisionCandidate>>,
+    hits:       Local<'s, Vec<SensorHit>>,
+}
+
+impl<'w, 's> SpatialQuery<'w, 's> {
+
+    pub fn world(&self) -> &CollisionWorld<'w> {
+        &self.world
+    }
+
+    pub fn raycast(&mut self, ray: &RayCaster, filter: &CollisionFilter, mode: RayQueryMode, out: &mut SliceVec<RayHit>) -> usize {
+        let mut candidates = get_buffer(&mut self.candidates);
+        let count = raycast_with(&self.world, ray, filter, mode, &mut candidates, out);
+        warn_if_full(&candidates);
+        count
+    }
+
+    pub fn raycast_first(&mut self, ray: &RayCaster, filter: &CollisionFilter) -> Option<RayHit> {
+        let mut hits = [RayHit::default()];
+        let mut out  = SliceVec::from_slice_len(&mut hits, 0);
+        self.raycast(ray, filter, RayQueryMode::First, &mut out);
+        out.first().copied()
+    }
+
+    // NOTE Hits are sorted by distance
+    pub fn shape_cast(&mut self, shape: &ShapeMoving, direction: Vec2, max_dist: f32, filter: &CollisionFilter, out: &mut SliceVec<SensorHit>) -> usize {
+        let mut candidates = get_buffer(&mut self.candidates);
+        let mut hits       = get_buffer(&mut self.hits);
+        test_movement_with(&self.world, shape, direction, max_dist, filter, &mut candidates, &mut hits);
+        warn_if_full(&candidates);
+        warn_if_full(&hits);
+
+        let count = hits.len().min(out.capacity() - out.len());
+        out.extend_from_slice(&hits[..count]);
+        count
+    }
+
+    pub fn shape_cast_first(&mut self, shape: &ShapeMoving, direction: Vec2, max_dist: f32, filter: &CollisionFilter) -> Option<SensorHit> {
+        let mut hits = [SensorHit::default()];
+        let mut out  = SliceVec::from_slice_len(&mut hits, 0);
+        self.shape_cast(shape, direction, max_dist, filter, &mut out);
+        out.first().copied()
+    }
+
+    pub fn overlaps(&mut self, shape: &ShapeMoving, filter: &CollisionFilter, out: &mut SliceVec<CollisionCandidate>) -> usize {
+        let mut candidates = get_buffer(&mut self.candidates);
+        self.world.find_candidates(shape, Vec2::ZERO, 0.0, filter, &mut candidates);
+        warn_if_full(&candidates);
+
+        let size = out.len();
+        for candidate in candidates.iter().filter(|v| shape.overlaps_static(&v.collider)) {
+            if out.len() == out.capacity() {
+                break;
+            }
+            out.push(candidate.clone());
+        }
+        out.len() - size
+    }
+
+}
+
+fn get_buffer<T: Default + Clone>(buffer: &mut Vec<T>) -> SliceVec<'_, T> {
+    buffer.resize(SPATIAL_QUERY_CAPACITY, T::default());
+    SliceVec::from_slice_len(buffer, 0)
+}
+
+fn warn_if_full<T>(buffer: &SliceVec<T>) {
+    static WARNED: AtomicBool = AtomicBool::new(false);
+    if buffer.len() == buffer.capacity() && !WARNED.swap(true, Ordering::Relaxed) {
+        warn!("Spatial query filled its buffer, anything past {SPATIAL_QUERY_CAPACITY} was dropped");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::*;
+    use nvm_collide::prelude::*;
+
+    use crate::{get_transform_2d, Collider};
+
+    #[test]
+    fn check_world_shape() {
+        let collider  = Collider::new_static(Ball::new(Vec2::new(1.0, 1.0), 0.25));
+        let transform = GlobalTransform::from(Transform::from_xyz(5.0, 0.0, 0.0).with_scale(Vec3::new(2.0, 3.0, 1.0)));
+        let shape = collider.get_world_shape(&transform);
+        assert!((shape.origin() - Vec2::new(7.0, 3.0)).length() < 1.0e-5);
+        assert!((shape.bounding_box().size - Vec2::splat(0.25)).length() < 1.0e-5);
+
+        let transform = GlobalTransform::from(Transform::from_xyz(5.0, 0.0, 0.0).with_scale(Vec3::splat(2.0)));
+        let shape = collider.get_world_shape(&transform);
+        assert!((shape.origin() - Vec2::new(7.0, 2.0)).length() < 1.0e-5);
+        assert!((shape.bounding_box().size - Vec2::splat(0.5)).length() < 1.0e-5);
+
+        // Round boxes fall back too, keeping their size and rotation
+        let collider = Collider::new_static(BoxAlignedRound::new(Vec2::new(1.0, 1.0), Vec2::ONE, 0.25));
+        let transform = GlobalTransform::from(Transform::from_xyz(5.0, 0.0, 0.0).with_scale(Vec3::new(2.0, 3.0, 1.0)));
+        let ShapeStatic::BoxAlignedRound(s) = collider.get_world_shape(&transform) else { panic!("Expected round box") };
+        assert!((s.origin - Vec2::new(7.0, 3.0)).length() < 1.0e-5);
+        assert!((s.size - Vec2::ONE).length() < 1.0e-5);
+
+        // Under a rotated parent with a scale that isn't uniform, boxes are
+        // sheared but stay where the transform puts them
+        let collider  = Collider::new_static(BoxAligned::new(Vec2::new(1.0, 0.0), Vec2::ONE));
+        let parent    = Transform::from_xyz(5.0, 0.0, 0.0).with_rotation(Quat::from_rotation_z(0.5)).with_scale(Vec3::new(1.0, 2.0, 1.0));
+        let transform = GlobalTransform::from(parent)*GlobalTransform::from(Transform::from_rotation(Quat::from_rotation_z(0.5)));
+        let ShapeStatic::PolygonSmall(s) = collider.get_world_shape(&transform) else { panic!("Expected polygon") };
+        let center = s.points().iter().sum::<Vec2>()/s.points().len() as f32;
+        assert!((center - get_transform_2d(&transform).transform_point2(Vec2::new(1.0, 0.0))).length() < 1.0e-4);
+    }
+}
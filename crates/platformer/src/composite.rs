@@ -0,0 +1,221 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use bevy::{prelude::*, ecs::system::SystemParam};
+use nvm_collide::prelude::*;
+use tinyvec::SliceVec;
+
+use crate::{CollisionBroadphase, CollisionCandidate, CollisionFilter, CollisionGrid, CollisionTree, Map};
+
+// NOTE Sources are queried in order into the same output, once it's full the
+//      rest are skipped. So put whatever matters most (ie. the map) first.
+
+impl<T: CollisionBroadphase + ?Sized> CollisionBroadphase for &T {
+    fn find_candidates(&self, collider: &ShapeMoving, direction: Vec2, max_dist: f32, filter: &CollisionFilter, out: &mut SliceVec<CollisionCandidate>) -> usize {
+        (**self).find_candidates(collider, direction, max_dist, filter, out)
+    }
+
+    fn find_candidates_ray(&self, ray: &RayCaster, filter: &CollisionFilter, out: &mut SliceVec<CollisionCandidate>) -> usize {
+        (**self).find_candidates_ray(ray, filter, out)
+    }
+}
+
+impl<T: CollisionBroadphase> CollisionBroadphase for Option<T> {
+    fn find_candidates(&self, collider: &ShapeMoving, direction: Vec2, max_dist: f32, filter: &CollisionFilter, out: &mut SliceVec<CollisionCandidate>) -> usize {
+        self.as_ref().map_or(0, |v| v.find_candidates(collider, direction, max_dist, filter, out))
+    }
+
+    fn find_candidates_ray(&self, ray: &RayCaster, filter: &CollisionFilter, out: &mut SliceVec<CollisionCandidate>) -> usize {
+        self.as_ref().map_or(0, |v| v.find_candidates_ray(ray, filter, out))
+    }
+}
+
+impl<T: CollisionBroadphase> CollisionBroadphase for [T] {
+    fn find_candidates(&self, collider: &ShapeMoving, direction: Vec2, max_dist: f32, filter: &CollisionFilter, out: &mut SliceVec<CollisionCandidate>) -> usize {
+        self.iter().map(|v| v.find_candidates(collider, direction, max_dist, filter, out)).sum()
+    }
+
+    fn find_candidates_ray(&self, ray: &RayCaster, filter: &CollisionFilter, out: &mut SliceVec<CollisionCandidate>) -> usize {
+        self.iter().map(|v| v.find_candidates_ray(ray, filter, out)).sum()
+    }
+}
+
+macro_rules! impl_broadphase_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: CollisionBroadphase),+> CollisionBroadphase for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn find_candidates(&self, collider: &ShapeMoving, direction: Vec2, max_dist: f32, filter: &CollisionFilter, out: &mut SliceVec<CollisionCandidate>) -> usize {
+                let ($($name,)+) = self;
+                0 $(+ $name.find_candidates(collider, direction, max_dist, filter, out))+
+            }
+
+            #[allow(non_snake_case)]
+            fn find_candidates_ray(&self, ray: &RayCaster, filter: &CollisionFilter, out: &mut SliceVec<CollisionCandidate>) -> usize {
+                let ($($name,)+) = self;
+                0 $(+ $name.find_candidates_ray(ray, filter, out))+
+            }
+        }
+    };
+}
+
+impl_broadphase_tuple!(A);
+impl_broadphase_tuple!(A, B);
+impl_broadphase_tuple!(A, B, C);
+impl_broadphase_tuple!(A, B, C, D);
+impl_broadphase_tuple!(A, B, C, D, E);
+impl_broadphase_tuple!(A, B, C, D, E, F);
+
+// ///////////// //
+// // Overlay // //
+// ///////////// //
+
+// NOTE For one-off colliders that don't live long enough to be worth putting
+//      in a tree, like attack hitboxes. Everything is tested, so keep it small.
+
+#[derive(Resource, Default)]
+pub struct CollisionOverlay {
+    items: Vec<CollisionCandidate>,
+}
+
+impl CollisionOverlay {
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn push(&mut self, item: CollisionCandidate) {
+        self.items.push(item);
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &CollisionCandidate> {
+        self.items.iter()
+    }
+
+}
+
+impl CollisionBroadphase for CollisionOverlay {
+    fn find_candidates(&self, collider: &ShapeMoving, direction: Vec2, max_dist: f32, filter: &CollisionFilter, out: &mut SliceVec<CollisionCandidate>) -> usize {
+        let offset = direction * max_dist;
+        let [min, max] = collider.bounding_box().bounds();
+        let [min, max] = [min.min(min + offset), max.max(max + offset)];
+
+        let size = out.len();
+        for item in self.items.iter() {
+            let [item_min, item_max] = item.collider.bounding_box().bounds();
+            let overlaps = item_min.cmple(max).all() && min.cmple(item_max).all();
+            if !overlaps || !filter.matches(item) {
+                continue;
+            }
+
+            if out.len() == out.capacity() {
+                break;
+            }
+            out.push(item.clone());
+        }
+
+        out.len() - size
+    }
+}
+
+// /////////// //
+// // World // //
+// /////////// //
+
+// NOTE Every source is optional, so a game only has to insert the ones it uses.
+//      With none of them inserted every query comes back empty, so motors
+//      (ie. motor_apply) will move straight through everything without warning.
+
+#[derive(SystemParam)]
+pub struct CollisionWorld<'w> {
+    map:     Option<Res<'w, Map>>,
+    tree:    Option<Res<'w, CollisionTree>>,
+    grid:    Option<Res<'w, CollisionGrid>>,
+    overlay: Option<Res<'w, CollisionOverlay>>,
+}
+
+impl<'w> CollisionWorld<'w> {
+    fn sources(&self) -> impl CollisionBroadphase + '_ {
+        (
+            self.map.as_deref(),
+            self.tree.as_deref(),
+            self.grid.as_deref(),
+            self.overlay.as_deref(),
+        )
+    }
+}
+
+impl<'w> CollisionBroadphase for CollisionWorld<'w> {
+    fn find_candidates(&self, collider: &ShapeMoving, direction: Vec2, max_dist: f32, filter: &CollisionFilter, out: &mut SliceVec<CollisionCandidate>) -> usize {
+        self.sources().find_candidates(collider, direction, max_dist, filter, out)
+    }
+
+    fn find_candidates_ray(&self, ray: &RayCaster, filter: &CollisionFilter, out: &mut SliceVec<CollisionCandidate>) -> usize {
+        self.sources().find_candidates_ray(ray, filter, out)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::{ecs::entity::Entity, math::Vec2};
+    use nvm_collide::prelude::*;
+    use tinyvec::SliceVec;
+
+    use crate::{CollisionBroadphase, CollisionCandidate, CollisionFilter, Map};
+    use super::CollisionOverlay;
+
+    fn get_overlay(entities: &[u32]) -> CollisionOverlay {
+        let mut overlay = CollisionOverlay::default();
+        for &entity in entities {
+            overlay.push(CollisionCandidate{
+                entity:   Some(Entity::from_raw(entity)),
+                collider: BoxAligned::new(Vec2::ZERO, Vec2::ONE).into(),
+                ..Default::default()
+            });
+        }
+        overlay
+    }
+
+    fn get_entities(broadphase: &(impl CollisionBroadphase + ?Sized), capacity: usize) -> Vec<u32> {
+        let collider: ShapeMoving = Ball::new(Vec2::ZERO, 0.5).into();
+        let mut buffer = vec![CollisionCandidate::default(); capacity];
+        let mut out    = SliceVec::from_slice_len(&mut buffer, 0);
+        let count = broadphase.find_candidates(&collider, Vec2::X, 1.0, &CollisionFilter::ALL, &mut out);
+        assert_eq!(count, out.len());
+
+        // Rays go through the same sources in the same order
+        let ray = RayCaster::new_segment(Vec2::ZERO, Vec2::X, 0.0, 1.0);
+        let mut ray_buffer = vec![CollisionCandidate::default(); capacity];
+        let mut ray_out    = SliceVec::from_slice_len(&mut ray_buffer, 0);
+        broadphase.find_candidates_ray(&ray, &CollisionFilter::ALL, &mut ray_out);
+        assert!(ray_out.iter().map(|v| v.entity).eq(out.iter().map(|v| v.entity)));
+
+        out.iter().filter_map(|v| v.entity).map(|v| v.index()).collect()
+    }
+
+    #[test]
+    fn check_composite() {
+        let [a, b, c] = [get_overlay(&[0, 1]), get_overlay(&[2, 3]), get_overlay(&[4, 5])];
+
+        assert_eq!(get_entities(&None::<CollisionOverlay>, 8), []);
+        assert_eq!(get_entities(&Some(&a), 8), [0, 1]);
+        assert_eq!(get_entities(&[&a, &b][..], 8), [0, 1, 2, 3]);
+        assert_eq!(get_entities(&(&a, None::<CollisionOverlay>, &c), 8), [0, 1, 4, 5]);
+        assert_eq!(get_entities(&(&b, (&a, Some(&c))), 8), [2, 3, 0, 1, 4, 5]);
+
+        // Once the output is full the rest of the sources are skipped
+        assert_eq!(get_entities(&(&a, &b, &c), 3), [0, 1, 2]);
+        assert_eq!(get_entities(&[&a, &b, &c][..], 2), [0, 1]);
+
+        // The map fills up on its two tiles before the overlay gets a turn
+        let map = Map::new(&["==="]);
+        assert_eq!(get_entities(&(&map, &a), 3), [0]);
+        assert_eq!(get_entities(&(&map, &a), 2), []);
+    }
+}
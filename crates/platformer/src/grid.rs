@@ -0,0 +1,297 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use bevy::{math::{IVec2, Vec2}, ecs::system::Resource, utils::HashMap};
+use nvm_collide::prelude::*;
+use tinyvec::SliceVec;
+
+use crate::{CollisionBroadphase, CollisionCandidate, CollisionFilter, is_bounds_overlapping};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CollisionGridId(usize, u32);
+
+#[derive(Debug, Clone)]
+struct GridItem {
+    cells: [IVec2; 2],
+    item:  CollisionCandidate,
+}
+
+// NOTE Every item is stored in each cell its bounds touch, this works best
+//      when most things are around the size of a cell or smaller.
+//      Slots get reused, so ids also hold a version that's bumped on every
+//      remove, otherwise an old id could reach whatever took its place.
+
+#[derive(Resource)]
+pub struct CollisionGrid {
+    cell_size: f32,
+    cells:     HashMap<IVec2, Vec<usize>>,
+    items:     Vec<Option<GridItem>>,
+    versions:  Vec<u32>,
+    free:      Vec<usize>,
+}
+
+impl Default for CollisionGrid {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+impl CollisionGrid {
+
+    pub fn new(cell_size: f32) -> Self {
+        Self{
+            cell_size,
+            cells:    HashMap::default(),
+            items:    Vec::new(),
+            versions: Vec::new(),
+            free:     Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len() - self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, id: CollisionGridId) -> Option<&CollisionCandidate> {
+        if !self.is_valid_id(id) {
+            return None;
+        }
+        self.items[id.0].as_ref().map(|v| &v.item)
+    }
+
+    pub fn insert(&mut self, item: CollisionCandidate) -> CollisionGridId {
+        let cells = self.find_cells(item.collider.bounding_box().bounds());
+        let index = if let Some(index) = self.free.pop() {
+            self.items[index] = Some(GridItem{ cells, item });
+            index
+        } else {
+            self.items.push(Some(GridItem{ cells, item }));
+            self.versions.push(0);
+            self.items.len() - 1
+        };
+
+        self.add_to_cells(index, cells);
+        CollisionGridId(index, self.versions[index])
+    }
+
+    pub fn remove(&mut self, id: CollisionGridId) -> Option<CollisionCandidate> {
+        if !self.is_valid_id(id) {
+            return None;
+        }
+
+        let item = self.items[id.0].take()?;
+        self.remove_from_cells(id.0, item.cells);
+        self.versions[id.0] = self.versions[id.0].wrapping_add(1);
+        self.free.push(id.0);
+        Some(item.item)
+    }
+
+    // NOTE Returns true if the item changed cells
+    pub fn update(&mut self, id: CollisionGridId, collider: ShapeStatic) -> bool {
+        let cells = self.find_cells(collider.bounding_box().bounds());
+        if !self.is_valid_id(id) {
+            return false;
+        }
+
+        let Some(item) = &mut self.items[id.0] else {
+            return false;
+        };
+
+        item.item.collider = collider;
+        let old = std::mem::replace(&mut item.cells, cells);
+        if old == cells {
+            return false;
+        }
+
+        self.remove_from_cells(id.0, old);
+        self.add_to_cells(id.0, cells);
+        true
+    }
+
+    pub fn query_region(&self, region: &BoxAligned, filter: &CollisionFilter, out: &mut SliceVec<CollisionCandidate>) -> usize {
+        let size   = out.len();
+        let region = region.bounds();
+        let range  = self.find_cells(region);
+
+        // Big queries (ie. long rays) would visit lots of empty cells, so walk the filled ones instead
+        let [min, max] = range;
+        let area = (max.x as i64 - min.x as i64 + 1) * (max.y as i64 - min.y as i64 + 1);
+        if area > self.cells.len() as i64 {
+            for (&cell, indices) in self.cells.iter() {
+                if cell.cmpge(min).all() && cell.cmple(max).all() && !self.query_cell(cell, indices, range, region, filter, out) {
+                    break;
+                }
+            }
+        } else {
+            'outer: for x in min.x..=max.x {
+                for y in min.y..=max.y {
+                    let cell = IVec2::new(x, y);
+                    if let Some(indices) = self.cells.get(&cell) {
+                        if !self.query_cell(cell, indices, range, region, filter, out) {
+                            break 'outer;
+                        }
+                    }
+                }
+            }
+        }
+
+        out.len() - size
+    }
+
+    // NOTE Returns false once the output is full
+    fn query_cell(&self, cell: IVec2, indices: &[usize], [min, _]: [IVec2; 2], region: [Vec2; 2], filter: &CollisionFilter, out: &mut SliceVec<CollisionCandidate>) -> bool {
+        for &index in indices {
+            let item = self.items[index].as_ref().unwrap();
+
+            // Items in many cells are only reported from the first one we share
+            if cell != item.cells[0].max(min) {
+                continue;
+            }
+
+            if !filter.matches(&item.item) || !is_bounds_overlapping(item.item.collider.bounding_box().bounds(), region) {
+                continue;
+            }
+
+            if out.len() == out.capacity() {
+                return false;
+            }
+            out.push(item.item.clone());
+        }
+        true
+    }
+
+    fn is_valid_id(&self, CollisionGridId(index, version): CollisionGridId) -> bool {
+        self.versions.get(index) == Some(&version)
+    }
+
+    fn find_cells(&self, [min, max]: [Vec2; 2]) -> [IVec2; 2] {
+        [
+            (min/self.cell_size).floor().as_ivec2(),
+            (max/self.cell_size).floor().as_ivec2(),
+        ]
+    }
+
+    fn add_to_cells(&mut self, index: usize, [min, max]: [IVec2; 2]) {
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                self.cells.entry(IVec2::new(x, y)).or_default().push(index);
+            }
+        }
+    }
+
+    fn remove_from_cells(&mut self, index: usize, [min, max]: [IVec2; 2]) {
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                let cell = IVec2::new(x, y);
+                let Some(indices) = self.cells.get_mut(&cell) else {
+                    continue;
+                };
+
+                if let Some(position) = indices.iter().position(|&v| v == index) {
+                    indices.swap_remove(position);
+                }
+
+                if indices.is_empty() {
+                    self.cells.remove(&cell);
+                }
+            }
+        }
+    }
+
+}
+
+impl CollisionBroadphase for CollisionGrid {
+    fn find_candidates(&self, collider: &ShapeMoving, direction: Vec2, max_dist: f32, filter: &CollisionFilter, out: &mut SliceVec<CollisionCandidate>) -> usize {
+        let offset = direction * max_dist;
+        let [min, max] = collider.bounding_box().bounds();
+        let [min, max] = [min.min(min + offset), max.max(max + offset)];
+        self.query_region(&BoxAligned::new(0.5*(min + max), 0.5*(max - min)), filter, out)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::{ecs::entity::Entity, math::Vec2};
+    use nvm_collide::{prelude::*, test_util::Rng};
+    use tinyvec::SliceVec;
+
+    use crate::{is_bounds_overlapping, CollisionCandidate, CollisionFilter, COLLISION_LAYERS_ALL};
+    use super::{CollisionGrid, CollisionGridId};
+
+    fn get_candidate(rng: &mut Rng, entity: u32) -> CollisionCandidate {
+        CollisionCandidate{
+            entity:   Some(Entity::from_raw(entity)),
+            collider: BoxAligned::new(rng.point(10.0), Vec2::new(rng.range(0.1, 1.5), rng.range(0.1, 1.5))).into(),
+            layers:   1 << (rng.next() % 2),
+            ..Default::default()
+        }
+    }
+
+    fn get_entities<'a>(items: impl Iterator<Item = &'a CollisionCandidate>) -> Vec<Entity> {
+        let mut entities: Vec<Entity> = items.filter_map(|v| v.entity).collect();
+        entities.sort();
+        entities
+    }
+
+    // NOTE The cells are smaller than most items, so they're spread across a
+    //      few cells each and have to be reported once. Big regions cover more
+    //      cells than are filled, so they take the other path.
+    #[test]
+    fn check_against_brute_force() {
+        let mut rng    = Rng(0x2545_f491);
+        let mut grid   = CollisionGrid::new(0.5);
+        let mut items  = Vec::<(CollisionGridId, CollisionCandidate)>::new();
+        let mut buffer = vec![CollisionCandidate::default(); 1024];
+        for i in 0..2000 {
+            match rng.next() % 4 {
+                0 | 1 => {
+                    let item = get_candidate(&mut rng, i);
+                    items.push((grid.insert(item.clone()), item));
+                },
+                2 if !items.is_empty() => {
+                    let (id, item) = items.swap_remove(rng.next() as usize % items.len());
+                    assert_eq!(grid.remove(id).and_then(|v| v.entity), item.entity);
+                    assert!(grid.get(id).is_none());
+                },
+                _ if !items.is_empty() => {
+                    let idx    = rng.next() as usize % items.len();
+                    let origin = items[idx].1.collider.origin() + rng.point(2.0);
+                    items[idx].1.collider.set_origin(origin);
+                    grid.update(items[idx].0, items[idx].1.collider.clone());
+                },
+                _ => {},
+            }
+            assert_eq!(grid.len(), items.len());
+
+            let extent = match rng.next() % 8 { 0 => 30.0, _ => 3.0 };
+            let filter = CollisionFilter::new(COLLISION_LAYERS_ALL, 1 + rng.next() % 3);
+            let region = BoxAligned::new(rng.point(10.0), Vec2::new(rng.range(0.0, extent), rng.range(0.0, extent)));
+            let mut out = SliceVec::from_slice_len(&mut buffer, 0);
+            let count = grid.query_region(&region, &filter, &mut out);
+            assert!(count < out.capacity());
+            let expected = items.iter()
+                .map(|(_, v)| v)
+                .filter(|v| filter.matches(v) && is_bounds_overlapping(v.collider.bounding_box().bounds(), region.bounds()));
+            assert_eq!(get_entities(out.iter()), get_entities(expected));
+        }
+    }
+
+    #[test]
+    fn check_stale_id() {
+        let mut rng  = Rng(0x9e37_79b9);
+        let mut grid = CollisionGrid::new(1.0);
+        let a = grid.insert(get_candidate(&mut rng, 0));
+        grid.remove(a);
+
+        // The new item reuses the old one's slot, but the old id can't reach it
+        let b = grid.insert(get_candidate(&mut rng, 1));
+        assert_eq!(a.0, b.0);
+        assert!(grid.get(a).is_none());
+        assert!(grid.remove(a).is_none());
+        assert!(!grid.update(a, BoxAligned::new(Vec2::splat(100.0), Vec2::ONE).into()));
+        assert_eq!(grid.get(b).and_then(|v| v.entity), Some(Entity::from_raw(1)));
+    }
+}
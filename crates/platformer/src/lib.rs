@@ -3,5 +3,20 @@
 mod map;
 pub use map::*;
 
+mod bvh;
+pub use bvh::*;
+
+mod grid;
+pub use grid::*;
+
+mod composite;
+pub use composite::*;
+
+mod collider;
+pub use collider::*;
+
+mod trigger;
+pub use trigger::*;
+
 mod player;
-pub use player::*;
\ No newline at end of file
+pub use player::*;
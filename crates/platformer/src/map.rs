@@ -3,13 +3,16 @@
 use bevy::{math::{IVec2, Vec2}, ecs::system::Resource};
 use nvm_collide::prelude::*;
 
-use crate::{CollisionBroadphase, CollisionCandidate};
+use crate::{CollisionBroadphase, CollisionCandidate, CollisionFilter, COLLISION_LAYERS_ALL};
+
+pub const MAP_COLLISION_LAYERS: u32 = 1 << 0;
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum TileKind {
     Empty,
     Full,
     Slope,
+    Ledge,
 }
 
 #[derive(Resource)]
@@ -33,6 +36,7 @@ impl Map {
                     ' ' => TileKind::Empty,
                     '=' => TileKind::Full,
                     '-' => TileKind::Slope,
+                    '_' => TileKind::Ledge,
                     _   => panic!("Invalid character"),
                 });
             }
@@ -67,9 +71,14 @@ impl Map {
                     Vec2::ONE
                 ).into())
             },
+            TileKind::Ledge => Some(BoxAligned::new(pos.as_vec2() + Vec2::new(0.0, 0.375), Vec2::new(0.5, 0.125)).into()),
         }
     }
 
+    pub fn get_one_way(&self, pos: IVec2) -> Option<Vec2> {
+        (self.get(pos) == TileKind::Ledge).then_some(Vec2::Y)
+    }
+
     pub fn width(&self) -> i32 {
         self.width 
     }
@@ -81,16 +90,18 @@ impl Map {
 }
 
 impl CollisionBroadphase for Map {
-    fn find_candidates(&self, collider: &ShapeMoving, direction: Vec2, max_dist: f32, out: &mut tinyvec::SliceVec<CollisionCandidate>) -> usize {
+    fn find_candidates(&self, collider: &ShapeMoving, direction: Vec2, max_dist: f32, filter: &CollisionFilter, out: &mut tinyvec::SliceVec<CollisionCandidate>) -> usize {
         let offset = direction * max_dist;
         let [mut min, mut max] = collider.bounding_box().bounds();
         min = min.min(min + offset);
         max = max.max(max + offset);
 
-        let x_min = min.x.floor() as i32;
-        let x_max = min.x.ceil()  as i32;
-        let y_min = min.y.floor() as i32;
-        let y_max = max.y.ceil()  as i32;
+        // Tiles are centered on their position, so pad by half a tile.
+        // Everything outside of the map is empty, so don't bother looking there.
+        let x_min = ((min.x - 0.5).floor() as i32).max(0);
+        let x_max = ((max.x + 0.5).ceil()  as i32).min(self.width);
+        let y_min = ((min.y - 0.5).floor() as i32).max(0);
+        let y_max = ((max.y + 0.5).ceil()  as i32).min(self.height);
 
         let size = out.len();
 
@@ -98,12 +109,74 @@ impl CollisionBroadphase for Map {
             for y in y_min..y_max {
                 // TODO opt check box overlap (?)
                 // TODO opt DDA (?)
-                if let Some(collider) = self.get_shape(IVec2::new(x, y)) {
-                    out.push(CollisionCandidate{ entity: None, collider });
+                let pos = IVec2::new(x, y);
+                if let Some(collider) = self.get_shape(pos) {
+                    let candidate = CollisionCandidate{ 
+                        entity:  None, 
+                        collider, 
+                        one_way: self.get_one_way(pos),
+                        layers:  MAP_COLLISION_LAYERS,
+                        mask:    COLLISION_LAYERS_ALL,
+                    };
+
+                    if !filter.matches(&candidate) {
+                        continue;
+                    }
+
+                    if out.len() == out.capacity() {
+                        return out.len() - size;
+                    }
+                    out.push(candidate);
                 }
             }
         }
 
         out.len() - size
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::math::{IVec2, Vec2};
+    use nvm_collide::prelude::*;
+    use tinyvec::SliceVec;
+
+    use crate::{CollisionBroadphase, CollisionCandidate, CollisionFilter};
+    use super::Map;
+
+    // NOTE The range used to start from the left edge on both sides, and
+    //      missed the half of each tile that sticks out past its position.
+    #[test]
+    fn check_find_candidates() {
+        let map = Map::new(&["======"; 6]);
+        let mut buffer = vec![CollisionCandidate::default(); 64];
+        for (origin, direction, max_dist) in [
+            (Vec2::new( 1.0, 1.0), Vec2::X,     2.5),
+            (Vec2::new( 4.0, 4.0), -Vec2::ONE,  2.0),
+            (Vec2::new( 2.8, 2.8), Vec2::ZERO,  0.0),
+            (Vec2::new(-3.0, 2.0), Vec2::X,    20.0),
+        ] {
+            let collider: ShapeMoving = BoxAligned::new(origin, Vec2::splat(0.3)).into();
+            let mut out = SliceVec::from_slice_len(&mut buffer, 0);
+            let count = map.find_candidates(&collider, direction.normalize_or_zero(), max_dist, &CollisionFilter::ALL, &mut out);
+            assert_eq!(count, out.len());
+
+            let found: Vec<IVec2> = out.iter().map(|v| v.collider.origin().as_ivec2()).collect();
+            let [min, max] = collider.bounding_box().bounds();
+            let offset = direction.normalize_or_zero()*max_dist;
+            let [min, max] = [min.min(min + offset), max.max(max + offset)];
+            for x in 0..map.width() {
+                for y in 0..map.height() {
+                    let pos = IVec2::new(x, y);
+                    let is_touching = (pos.as_vec2() + Vec2::splat(0.5)).cmpgt(min).all() && (pos.as_vec2() - Vec2::splat(0.5)).cmplt(max).all();
+                    assert!(!is_touching || found.contains(&pos), "Missed {pos} from {origin}");
+                }
+            }
+
+            let mut unique = found.clone();
+            unique.sort_by_key(|v| (v.x, v.y));
+            unique.dedup();
+            assert_eq!(unique.len(), found.len());
+        }
+    }
+}
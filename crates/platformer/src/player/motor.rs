@@ -4,7 +4,7 @@ use bevy::prelude::*;
 use nvm_collide::prelude::*;
 use tinyvec::SliceVec;
 
-use crate::{test_movement_with, CollisionBroadphase, Map, Buffer, CollisionCandidate, SensorHit};
+use crate::{test_movement_with, CollisionBroadphase, CollisionFilter, CollisionWorld, Buffer, CollisionCandidate, SensorHit};
 
 #[derive(Debug, Component, Clone, Copy)]
 pub struct PlatformerMotorConfig {
@@ -12,27 +12,47 @@ pub struct PlatformerMotorConfig {
     pub dist_step:   f32,
     pub dist_snap:   f32,
     pub dist_ground: f32,
+    pub filter:      CollisionFilter,
 }
 
 #[derive(Debug, Component, Clone, Copy)]
 pub struct PlatformerMotor {
-    pub velocity:   Vec2,
-    pub allow_step: bool,
-    pub allow_snap: bool,
+    pub velocity:     Vec2,
+    pub allow_step:   bool,
+    pub allow_snap:   bool,
+    pub drop_through: bool,
 }
 
 #[derive(Debug, Component, Clone, Copy)]
 pub struct PlatformerState {
     pub last_motion: Vec2,
+    pub last_push:   Vec2,
     pub on_ground:   bool,
     pub ground_norm: Vec2,
     pub hit_norm:    Option<Vec2>,
 }
 
 pub fn motor_apply(
-    mut q_motors: Query<(&GlobalTransform, &mut Transform, &mut PlatformerState, &mut PlatformerMotor, &PlatformerMotorConfig)>, 
-    r_map:  Res<Map>,
-    r_time: Res<Time>,
+    mut q_motors: Query<(Entity, &GlobalTransform, &mut Transform, &mut PlatformerState, &mut PlatformerMotor, &PlatformerMotorConfig)>, 
+    r_world: CollisionWorld,
+    r_time:  Res<Time>,
+) {
+    apply_motors(&r_world, &mut q_motors, r_time.delta_seconds());
+}
+
+// NOTE For games that move through their own world, rather than the built in sources
+pub fn motor_apply_with<B: Resource + CollisionBroadphase>(
+    mut q_motors: Query<(Entity, &GlobalTransform, &mut Transform, &mut PlatformerState, &mut PlatformerMotor, &PlatformerMotorConfig)>, 
+    r_world: Res<B>,
+    r_time:  Res<Time>,
+) {
+    apply_motors(&*r_world, &mut q_motors, r_time.delta_seconds());
+}
+
+fn apply_motors(
+    broadphase: &impl CollisionBroadphase,
+    q_motors:   &mut Query<(Entity, &GlobalTransform, &mut Transform, &mut PlatformerState, &mut PlatformerMotor, &PlatformerMotorConfig)>, 
+    dt:         f32,
 ) {
     // TODO move into component to avoid realloc
     // TODO determine size
@@ -41,11 +61,12 @@ pub fn motor_apply(
     let mut hits       = Buffer::new(32);
     let mut hits       = hits.get(); 
 
-    for (global_transform, mut transform, mut state, mut motor, config) in q_motors.iter_mut() {
+    for (entity, global_transform, mut transform, mut state, mut motor, config) in q_motors.iter_mut() {
         candidates.clear();
         hits.clear();
 
-        let new_state = do_motor_apply(&*r_map, config, &motor, r_time.delta_seconds(), global_transform.translation().truncate(), &mut candidates, &mut hits);
+        let config    = PlatformerMotorConfig{ filter: config.filter.with_exclude(Some(entity)), ..*config };
+        let new_state = do_motor_apply(broadphase, &config, &motor, dt, global_transform.translation().truncate(), &mut candidates, &mut hits);
         if let Some(hit_norm) = new_state.hit_norm {
             let motor_velocity = motor.velocity;
             motor.velocity += motor_velocity.normalize()*motor_velocity.dot(hit_norm);
@@ -60,7 +81,7 @@ pub fn motor_apply(
 
         // TODO slide
 
-        transform.translation += new_state.last_motion.extend(0.0);
+        transform.translation += (new_state.last_push + new_state.last_motion).extend(0.0);
     }
 }
 
@@ -73,20 +94,30 @@ fn do_motor_apply(
     candidates: &mut SliceVec<CollisionCandidate>,
     hits:       &mut SliceVec<SensorHit>,
 ) -> PlatformerState {
-    let mut collider = create_collider(config, motor, origin);
+    let mut collider: ShapeMoving = create_collider(config, motor, origin).into();
+    let filter = config.filter.with_drop_through(motor.drop_through);
     let mut new_state = PlatformerState{
         on_ground:   false,
         ground_norm: Vec2::Y,
         last_motion: dt*motor.velocity,
+        last_push:   Vec2::ZERO,
         hit_norm:    None,
     };
 
+    // We might've started the tick embedded in something (spawning, moving
+    // platforms, float error), the sweeps can't recover from that so push out first
+    if let Some(push) = find_depenetration(broadphase, &collider, &filter, candidates) {
+        collider.set_origin(collider.origin() + push);
+        new_state.last_push = push;
+    }
+
     // Check motion
+    candidates.clear();
     let motion_dist = new_state.last_motion.length();
     if motion_dist > 0.0 {
         let motion_dir  = new_state.last_motion/motion_dist;
         // Check for collision
-        if test_movement_with(broadphase, &collider, motion_dir, motion_dist, candidates, hits) != 0 {
+        if test_movement_with(broadphase, &collider, motion_dir, motion_dist, &filter, candidates, hits) != 0 {
             let hit = hits.first().unwrap();
             new_state.on_ground   = is_ground_norm(hit.normal);
             new_state.ground_norm = if new_state.on_ground { hit.normal } else { Vec2::Y };
@@ -108,7 +139,7 @@ fn do_motor_apply(
     if can_step {
         candidates.clear();
         hits.clear();
-        if let Some([origin_new, ground_norm]) = find_step(broadphase, &collider, config.dist_step, candidates, hits) {
+        if let Some([origin_new, ground_norm]) = find_step(broadphase, &collider, config.dist_step, &filter, candidates, hits) {
             collider.set_origin(origin_new);
             new_state.on_ground   = true;
             new_state.ground_norm = ground_norm;
@@ -126,12 +157,12 @@ fn do_motor_apply(
             // TODO how should we be applying the ground detection skin here... if at all? does it matter?
             //      we don't handle it in the motion situation... ugh... hate this stuff... come onnnnn...
             let dist_step = if motor.allow_step { config.dist_step } else { 0.0 };
-            if let Some([origin_new, ground_norm]) = find_snap(broadphase, &collider, config.dist_snap, dist_step, candidates, hits) {
+            if let Some([origin_new, ground_norm]) = find_snap(broadphase, &collider, config.dist_snap, dist_step, &filter, candidates, hits) {
                 collider.set_origin(origin_new);
                 new_state.on_ground   = true;
                 new_state.ground_norm = ground_norm;
             }
-        } else if let Some(ground_norm) = find_ground(broadphase, &collider, config.dist_ground, candidates, hits) {
+        } else if let Some(ground_norm) = find_ground(broadphase, &collider, config.dist_ground, &filter, candidates, hits) {
             new_state.on_ground   = true;
             new_state.ground_norm = ground_norm;
         }
@@ -142,14 +173,39 @@ fn do_motor_apply(
 
 // TODO (?) make these find functions return a modified sensor hit
 
-fn find_step(
+fn find_depenetration(
     broadphase: &impl CollisionBroadphase,
     collider:   &ShapeMoving,
-    dist_step:  f32,
+    filter:     &CollisionFilter,
     candidates: &mut SliceVec<CollisionCandidate>,
-    hits:       &mut SliceVec<SensorHit>,
+) -> Option<Vec2> {
+    if broadphase.find_candidates(collider, Vec2::ZERO, 0.0, filter, candidates) == 0 {
+        return None;
+    }
+
+    // Resolve one at a time, so we don't get pushed out of one shape into another.
+    // One-way shapes never push, we're allowed to be partway through them.
+    let mut collider = *collider;
+    let mut push     = Vec2::ZERO;
+    for candidate in candidates.iter().filter(|v| v.one_way.is_none()) {
+        if let Some(offset) = collider.find_depenetration_static(&candidate.collider) {
+            collider.set_origin(collider.origin() + offset);
+            push += offset;
+        }
+    }
+
+    (push != Vec2::ZERO).then_some(push)
+}
+
+fn find_step(
+    broadphase:   &impl CollisionBroadphase,
+    collider:     &ShapeMoving,
+    dist_step:    f32,
+    filter:       &CollisionFilter,
+    candidates:   &mut SliceVec<CollisionCandidate>,
+    hits:         &mut SliceVec<SensorHit>,
 ) -> Option<[Vec2; 2]> {
-    if test_movement_with(broadphase, collider, -Vec2::Y, dist_step, candidates, hits) != 0 {
+    if test_movement_with(broadphase, collider, -Vec2::Y, dist_step, filter, candidates, hits) != 0 {
         if let Some(hit) = hits.iter().find(is_valid_floor) {
             return Some([
                 collider.origin() + (hit.distance - dist_step).max(0.0),
@@ -161,14 +217,15 @@ fn find_step(
 }
 
 fn find_snap(
-    broadphase: &impl CollisionBroadphase,
-    collider:   &ShapeMoving,
-    dist_snap:  f32,
-    dist_float: f32,
-    candidates: &mut SliceVec<CollisionCandidate>,
-    hits:       &mut SliceVec<SensorHit>,
+    broadphase:   &impl CollisionBroadphase,
+    collider:     &ShapeMoving,
+    dist_snap:    f32,
+    dist_float:   f32,
+    filter:       &CollisionFilter,
+    candidates:   &mut SliceVec<CollisionCandidate>,
+    hits:         &mut SliceVec<SensorHit>,
 ) -> Option<[Vec2; 2]> {
-    if test_movement_with(broadphase, collider, -Vec2::Y, dist_snap + dist_float, candidates, hits) != 0 {
+    if test_movement_with(broadphase, collider, -Vec2::Y, dist_snap + dist_float, filter, candidates, hits) != 0 {
         if let Some(hit) = hits.iter().find(is_valid_floor) {
             return Some([
                 collider.origin() + (hit.distance - dist_float).min(0.0),
@@ -180,13 +237,14 @@ fn find_snap(
 }
 
 fn find_ground(
-    broadphase:  &impl CollisionBroadphase,
-    collider:    &ShapeMoving,
-    dist_ground: f32,
-    candidates:  &mut SliceVec<CollisionCandidate>,
-    hits:        &mut SliceVec<SensorHit>,
+    broadphase:   &impl CollisionBroadphase,
+    collider:     &ShapeMoving,
+    dist_ground:  f32,
+    filter:       &CollisionFilter,
+    candidates:   &mut SliceVec<CollisionCandidate>,
+    hits:         &mut SliceVec<SensorHit>,
 ) -> Option<Vec2> {
-    if test_movement_with(broadphase, collider, -Vec2::Y, dist_ground, candidates, hits) != 0 {
+    if test_movement_with(broadphase, collider, -Vec2::Y, dist_ground, filter, candidates, hits) != 0 {
         if let Some(hit) = hits.iter().find(is_valid_floor) {
             return Some(hit.normal);
         }
@@ -195,12 +253,14 @@ fn find_ground(
     None
 }
 
-fn create_collider(config: &PlatformerMotorConfig, motor: &PlatformerMotor, origin: Vec2) -> ShapeMoving {
+// NOTE Shorter than the motor's size by the step height, so it can climb
+//      anything below that. Triggers check against this too.
+pub(crate) fn create_collider(config: &PlatformerMotorConfig, motor: &PlatformerMotor, origin: Vec2) -> BoxAligned {
     let dist_step = if motor.allow_step { config.dist_step } else { 0.0 };
     BoxAligned::new(
         Vec2::new(     origin.x,      origin.y - dist_step*0.5),
         Vec2::new(config.size.x, config.size.y - dist_step*0.5)
-    ).into()
+    )
 }
 
 fn is_valid_floor(hit: &&SensorHit) -> bool {
@@ -7,12 +7,16 @@ use tinyvec::SliceVec;
 mod util;
 pub use util::*;
 
+mod query;
+pub use query::*;
+
 #[derive(Debug, Clone, Copy)]
 pub struct SensorHit {
     pub entity:   Option<Entity>,
     pub distance: f32,
     pub point:    Vec2,
     pub normal:   Vec2,
+    pub contact:  Vec2,
 }
 
 impl Default for SensorHit {
@@ -22,14 +26,18 @@ impl Default for SensorHit {
             distance: 0.0,
             point:  Vec2::ZERO,
             normal: Vec2::ZERO,
+            contact: Vec2::ZERO,
         }
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub struct CollisionCandidate {
     pub entity:   Option<Entity>,
     pub collider: ShapeStatic,
+    pub one_way:  Option<Vec2>,
+    pub layers:   u32,
+    pub mask:     u32,
 }
 
 impl Default for CollisionCandidate {
@@ -37,18 +45,72 @@ impl Default for CollisionCandidate {
         Self{
             entity: None,
             collider: Ball::new(Vec2::ZERO, 0.0).into(),
+            one_way:  None,
+            layers:   COLLISION_LAYERS_ALL,
+            mask:     COLLISION_LAYERS_ALL,
         }
     }
 }
 
+pub const COLLISION_LAYERS_ALL: u32 = u32::MAX;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CollisionFilter {
+    pub layers:       u32,
+    pub mask:         u32,
+    pub drop_through: bool,
+    pub exclude:      Option<Entity>,
+}
+
+impl Default for CollisionFilter {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+impl CollisionFilter {
+
+    pub const ALL: Self = Self::new(COLLISION_LAYERS_ALL, COLLISION_LAYERS_ALL);
+
+    pub const fn new(layers: u32, mask: u32) -> Self {
+        Self{ layers, mask, drop_through: false, exclude: None }
+    }
+
+    pub const fn with_drop_through(self, drop_through: bool) -> Self {
+        Self{ drop_through, ..self }
+    }
+
+    // NOTE So an entity with its own collider doesn't run into itself
+    pub const fn with_exclude(self, exclude: Option<Entity>) -> Self {
+        Self{ exclude, ..self }
+    }
+
+    // NOTE Both sides have to agree, so a ray that only wants interactables
+    //      can use a mask of just that layer and ignore everything else.
+    pub fn matches(&self, candidate: &CollisionCandidate) -> bool {
+        (self.mask & candidate.layers) != 0 
+            && (candidate.mask & self.layers) != 0 
+            && !(self.drop_through && candidate.one_way.is_some())
+            && (self.exclude.is_none() || self.exclude != candidate.entity)
+    }
+
+}
+
 pub trait CollisionBroadphase {
-    fn find_candidates(&self, collider: &ShapeMoving, direction: Vec2, max_dist: f32, out: &mut SliceVec<CollisionCandidate>) -> usize;
+    // NOTE Implementations should only output candidates that match the filter
+    fn find_candidates(&self, collider: &ShapeMoving, direction: Vec2, max_dist: f32, filter: &CollisionFilter, out: &mut SliceVec<CollisionCandidate>) -> usize;
+
+    // NOTE By default this sweeps a point along the ray, which gets very loose for long diagonal rays
+    fn find_candidates_ray(&self, ray: &RayCaster, filter: &CollisionFilter, out: &mut SliceVec<CollisionCandidate>) -> usize {
+        let collider: ShapeMoving = Ball::new(ray.origin(), 0.0).into();
+        self.find_candidates(&collider, ray.direction(), ray.max_distance(), filter, out)
+    }
 }
 
 
-pub struct Buffer<T: Default + Copy>(Box<[T]>);
+pub struct Buffer<T: Default + Clone>(Box<[T]>);
 
-impl<T: Default + Copy> Buffer<T> {
+impl<T: Default + Clone> Buffer<T> {
 
     pub fn new(size: usize) -> Self {
         let mut buffer = Vec::with_capacity(size);
@@ -0,0 +1,134 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use bevy::prelude::*;
+use nvm_collide::prelude::*;
+use tinyvec::SliceVec;
+
+use crate::{CollisionCandidate, CollisionBroadphase, CollisionFilter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RayQueryMode {
+    All,
+    First,
+    Any,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RayHit {
+    pub entity:       Option<Entity>,
+    pub intersection: RayIntersection,
+}
+
+impl Default for RayHit {
+    fn default() -> Self {
+        Self{
+            entity: None,
+            intersection: RayIntersection{ distance: 0.0, point: Vec2::ZERO, normal: Vec2::ZERO },
+        }
+    }
+}
+
+// NOTE Rays that start inside a shape report its entry point behind the origin,
+//      so those hits have a negative distance and sort first.
+//      In All mode the closest hits are kept when the output fills up.
+//      The ray should be a segment, otherwise the broadphase searches forever.
+
+pub fn raycast_with(
+    broadphase: &impl CollisionBroadphase, 
+    raycaster: &RayCaster, 
+    filter: &CollisionFilter, 
+    mode: RayQueryMode,
+    candidates: &mut SliceVec<CollisionCandidate>,
+    out:        &mut SliceVec<RayHit>
+) -> usize {
+    debug_assert_eq!(out.len(), 0);
+
+    if broadphase.find_candidates_ray(raycaster, filter, candidates) == 0 {
+        return 0;
+    }
+
+    for candidate in candidates.iter() {
+        let hit = match candidate.one_way {
+            Some(normal) => raycaster.test_one_way(&candidate.collider, normal),
+            None         => raycaster.test(&candidate.collider),
+        };
+
+        let Some([enter, _]) = hit else {
+            continue;
+        };
+
+        let hit = RayHit{ entity: candidate.entity, intersection: enter };
+        match mode {
+            RayQueryMode::Any => {
+                out.push(hit);
+                break;
+            },
+            RayQueryMode::First => {
+                match out.first_mut() {
+                    Some(first) if first.intersection.distance <= enter.distance => {},
+                    Some(first) => *first = hit,
+                    None => out.push(hit),
+                }
+            },
+            RayQueryMode::All => {
+                let idx = out.partition_point(|v| v.intersection.distance < enter.distance);
+                if out.len() == out.capacity() {
+                    if idx == out.len() {
+                        continue;
+                    }
+                    out.pop();
+                }
+                out.insert(idx, hit);
+            },
+        }
+    }
+
+    out.len()
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::math::Vec2;
+    use nvm_collide::prelude::*;
+    use tinyvec::SliceVec;
+
+    use crate::{CollisionCandidate, CollisionFilter, Map};
+    use super::{raycast_with, RayHit, RayQueryMode};
+
+    fn get_distances(map: &Map, ray: &RayCaster, mode: RayQueryMode, capacity: usize) -> Vec<f32> {
+        let mut candidates = vec![CollisionCandidate::default(); 16];
+        let mut hits       = vec![RayHit::default(); capacity];
+        let mut candidates = SliceVec::from_slice_len(&mut candidates, 0);
+        let mut out        = SliceVec::from_slice_len(&mut hits, 0);
+        let count = raycast_with(map, ray, &CollisionFilter::ALL, mode, &mut candidates, &mut out);
+        assert_eq!(count, out.len());
+        out.iter().map(|v| v.intersection.distance).collect()
+    }
+
+    // NOTE The map hands out candidates from left to right, so a ray going 
+    //      left sees the furthest tile first.
+    #[test]
+    fn check_raycast_with() {
+        let map = Map::new(&["= = = "]);
+        let ray = RayCaster::new_segment(Vec2::new(6.0, 0.0), -Vec2::X, 0.0, 10.0);
+
+        assert_eq!(get_distances(&map, &ray, RayQueryMode::All,   8), [1.5, 3.5, 5.5]);
+        assert_eq!(get_distances(&map, &ray, RayQueryMode::All,   2), [1.5, 3.5]);
+        assert_eq!(get_distances(&map, &ray, RayQueryMode::First, 8), [1.5]);
+        assert_eq!(get_distances(&map, &ray, RayQueryMode::Any,   8), [5.5]);
+
+        // Tiles past the end of the segment are ignored
+        let ray = RayCaster::new_segment(Vec2::new(6.0, 0.0), -Vec2::X, 0.0, 3.0);
+        assert_eq!(get_distances(&map, &ray, RayQueryMode::All,   8), [1.5]);
+        assert_eq!(get_distances(&map, &ray, RayQueryMode::First, 8), [1.5]);
+
+        // Starting inside a tile puts it first, behind the origin
+        let ray = RayCaster::new_segment(Vec2::new(4.0, 0.0), -Vec2::X, 0.0, 10.0);
+        assert_eq!(get_distances(&map, &ray, RayQueryMode::All,   8), [-0.5, 1.5, 3.5]);
+        assert_eq!(get_distances(&map, &ray, RayQueryMode::First, 8), [-0.5]);
+
+        // Nothing to hit
+        let ray = RayCaster::new_segment(Vec2::new(6.0, 2.0), -Vec2::X, 0.0, 10.0);
+        assert!(get_distances(&map, &ray, RayQueryMode::Any, 8).is_empty());
+    }
+}
@@ -4,37 +4,33 @@ use bevy::prelude::*;
 use nvm_collide::prelude::*;
 use tinyvec::SliceVec;
 
-use crate::{CollisionCandidate, CollisionBroadphase, SensorHit};
+use crate::{CollisionCandidate, CollisionBroadphase, CollisionFilter, SensorHit};
 
 pub fn test_movement_with(
     broadphase: &impl CollisionBroadphase, 
     collider: &ShapeMoving, 
     motion_dir: Vec2, 
     motion_dist: f32, 
+    filter: &CollisionFilter, 
     candidates: &mut SliceVec<CollisionCandidate>,
     out:        &mut SliceVec<SensorHit>
 ) -> usize {
     debug_assert_eq!(out.len(), 0);
 
-    if broadphase.find_candidates(collider, motion_dir, motion_dist, candidates) == 0 {
+    if broadphase.find_candidates(collider, motion_dir, motion_dist, filter, candidates) == 0 {
         return 0;
     }
 
-    let raycaster = RayCaster::new(collider.origin(), motion_dir);
+    // Hits that started behind us still count if we're inside them
+    let raycaster = RayCaster::new_segment(collider.origin(), motion_dir, 0.0, motion_dist);
     for candidate in candidates.iter() {
         let combined = ShapeCombined::between_moving_and_static(collider, &candidate.collider);
-        if let Some([enter, exit]) = raycaster.test(&combined) {
-            // Exclude collisions past max distance
-            if enter.distance > motion_dist {
-                continue;
-            }
-
-            // Exclude collisions that happen behind us but don't intersect at all
-            if exit.distance < 0.0 {
-                // TODO OPT fast path where we don't need the exit calculated
-                continue;
-            }
+        let hit = match candidate.one_way {
+            Some(normal) => raycaster.test_one_way(&combined, normal),
+            None         => raycaster.test(&combined),
+        };
 
+        if let Some([enter, _]) = hit {
             // Sorted insert
             out.insert(
                 out.partition_point(|v| v.distance < enter.distance),
@@ -42,7 +38,8 @@ pub fn test_movement_with(
                     entity:   candidate.entity, 
                     distance: enter.distance, 
                     point:    enter.point, 
-                    normal:   enter.normal 
+                    normal:   enter.normal,
+                    contact:  collider.find_contact_static(enter.point - collider.origin(), enter.normal, &candidate.collider),
                 }
             );
         }
@@ -0,0 +1,228 @@
+// Copyright 2023 Natalie Baker // AGPLv3 //
+
+use bevy::{prelude::*, utils::HashMap};
+use nvm_collide::prelude::*;
+use tinyvec::SliceVec;
+
+use crate::{create_collider, get_transform_2d, warn_transform_fallback, Collider, CollisionCandidate, CollisionFilter, PlatformerMotor, PlatformerMotorConfig, SpatialQuery, COLLISION_LAYERS_ALL, MAP_COLLISION_LAYERS, SPATIAL_QUERY_CAPACITY};
+
+// NOTE Triggers aren't part of the collision world, so nothing is blocked by
+//      them. They pick up colliders in the world and motors, but not the map.
+
+#[derive(Debug, Component, Clone, Copy)]
+pub struct Trigger {
+    pub shape:  ShapeMoving,
+    pub layers: u32,
+    pub mask:   u32,
+}
+
+impl Trigger {
+
+    pub fn new(shape: impl Into<ShapeMoving>) -> Self {
+        Self{
+            shape:  shape.into(),
+            layers: COLLISION_LAYERS_ALL,
+            mask:   COLLISION_LAYERS_ALL & !MAP_COLLISION_LAYERS,
+        }
+    }
+
+    pub fn with_layers(self, layers: u32, mask: u32) -> Self {
+        Self{ layers, mask, ..self }
+    }
+
+    // NOTE Same as Collider::get_world_shape
+    pub fn get_world_shape(&self, transform: &GlobalTransform) -> ShapeMoving {
+        let affine = get_transform_2d(transform);
+        self.shape.transformed(&affine).unwrap_or_else(|error| {
+            warn_transform_fallback(error);
+            let mut shape = self.shape;
+            shape.set_origin(affine.transform_point2(shape.origin()));
+            shape
+        })
+    }
+
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerPhase {
+    Enter,
+    Stay,
+    Exit,
+}
+
+#[derive(Debug, Event, Clone, Copy, PartialEq, Eq)]
+pub struct TriggerEvent {
+    pub trigger: Entity,
+    pub other:   Entity,
+    pub phase:   TriggerPhase,
+}
+
+// NOTE Everything currently inside each trigger, as of the last update.
+
+#[derive(Resource, Default)]
+pub struct TriggerContacts {
+    contacts: HashMap<Entity, Vec<Entity>>,
+}
+
+impl TriggerContacts {
+
+    pub fn get(&self, trigger: Entity) -> &[Entity] {
+        self.contacts.get(&trigger).map_or(&[], |v| v.as_slice())
+    }
+
+    pub fn contains(&self, trigger: Entity, other: Entity) -> bool {
+        self.get(trigger).contains(&other)
+    }
+
+}
+
+pub(crate) fn trigger_update(
+    q_triggers:     Query<(Entity, &Trigger, &GlobalTransform)>,
+    q_motors:       Query<(Entity, &PlatformerMotorConfig, &PlatformerMotor, &GlobalTransform), Without<Collider>>,
+    mut p_spatial:  SpatialQuery,
+    mut r_contacts: ResMut<TriggerContacts>,
+    mut r_removed:  RemovedComponents<Trigger>,
+    mut e_trigger:  EventWriter<TriggerEvent>,
+    mut l_buffer:   Local<Vec<CollisionCandidate>>,
+) {
+    // Removed triggers let go of everything in them
+    for trigger in r_removed.read() {
+        for other in r_contacts.contacts.remove(&trigger).unwrap_or_default() {
+            e_trigger.send(TriggerEvent{ trigger, other, phase: TriggerPhase::Exit });
+        }
+    }
+
+    l_buffer.resize(SPATIAL_QUERY_CAPACITY, CollisionCandidate::default());
+    for (trigger, config, transform) in q_triggers.iter() {
+        let shape  = config.get_world_shape(transform);
+        let filter = CollisionFilter::new(config.layers, config.mask).with_exclude(Some(trigger));
+
+        let mut overlaps = SliceVec::from_slice_len(&mut l_buffer, 0);
+        p_spatial.overlaps(&shape, &filter, &mut overlaps);
+
+        let mut current: Vec<Entity> = overlaps.iter().filter_map(|v| v.entity).collect();
+        current.extend(q_motors.iter()
+            .map(|(other, config, motor, transform)| CollisionCandidate{
+                entity:   Some(other),
+                collider: create_collider(config, motor, transform.translation().truncate()).into(),
+                layers:   config.filter.layers,
+                mask:     config.filter.mask,
+                ..Default::default()
+            })
+            .filter(|candidate| filter.matches(candidate) && shape.overlaps_static(&candidate.collider))
+            .filter_map(|candidate| candidate.entity)
+        );
+        current.sort();
+        current.dedup();
+
+        let previous = r_contacts.contacts.remove(&trigger).unwrap_or_default();
+        for &other in current.iter() {
+            let phase = if previous.binary_search(&other).is_ok() { TriggerPhase::Stay } else { TriggerPhase::Enter };
+            e_trigger.send(TriggerEvent{ trigger, other, phase });
+        }
+
+        for &other in previous.iter().filter(|v| current.binary_search(v).is_err()) {
+            e_trigger.send(TriggerEvent{ trigger, other, phase: TriggerPhase::Exit });
+        }
+
+        if !current.is_empty() {
+            r_contacts.contacts.insert(trigger, current);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::*;
+    use nvm_collide::prelude::*;
+
+    use crate::{CollidePlugin, Collider, CollisionFilter, PlatformerMotor, PlatformerMotorConfig, Trigger, TriggerContacts, TriggerEvent, TriggerPhase};
+
+    #[test]
+    fn check_trigger_events() {
+        let mut app = App::new();
+        app.add_plugins((TransformPlugin, CollidePlugin));
+
+        let trigger = app.world.spawn((
+            Trigger::new(BoxAligned::new(Vec2::ZERO, Vec2::splat(1.0))),
+            TransformBundle::default(),
+        )).id();
+
+        // Colliders come through the world, motors are checked directly
+        let collider = app.world.spawn((
+            Collider::new_static(Ball::new(Vec2::ZERO, 0.25)),
+            TransformBundle::default(),
+        )).id();
+        let motor = app.world.spawn((
+            PlatformerMotorConfig{
+                size:        Vec2::splat(0.25),
+                dist_step:   0.0,
+                dist_snap:   0.0,
+                dist_ground: 0.0,
+                filter:      CollisionFilter::ALL,
+            },
+            get_motor(),
+            TransformBundle::default(),
+        )).id();
+
+        for (x, phase) in [
+            (-3.0, None),
+            (-1.0, Some(TriggerPhase::Enter)),
+            ( 0.0, Some(TriggerPhase::Stay)),
+            ( 1.0, Some(TriggerPhase::Stay)),
+            ( 3.0, Some(TriggerPhase::Exit)),
+            ( 5.0, None),
+        ] {
+            for entity in [collider, motor] {
+                app.world.get_mut::<Transform>(entity).unwrap().translation.x = x;
+            }
+            app.update();
+
+            let mut events: Vec<_> = app.world.resource_mut::<Events<TriggerEvent>>().drain().collect();
+            events.sort_by_key(|v| v.other);
+            let expected: Vec<_> = phase.into_iter()
+                .flat_map(|phase| [collider, motor].map(|other| TriggerEvent{ trigger, other, phase }))
+                .collect();
+            assert_eq!(events, expected, "at {x}");
+        }
+    }
+
+    #[test]
+    fn check_trigger_motor_step() {
+        let mut app = App::new();
+        app.add_plugins((TransformPlugin, CollidePlugin));
+
+        // Just above the motor's collider, which leaves room under it to step
+        let trigger = app.world.spawn((
+            Trigger::new(BoxAligned::new(Vec2::new(0.0, 0.75), Vec2::splat(0.25))),
+            TransformBundle::default(),
+        )).id();
+        let motor = app.world.spawn((
+            PlatformerMotorConfig{
+                size:        Vec2::new(0.25, 0.75),
+                dist_step:   0.5,
+                dist_snap:   0.0,
+                dist_ground: 0.0,
+                filter:      CollisionFilter::ALL,
+            },
+            get_motor(),
+            TransformBundle::default(),
+        )).id();
+
+        app.update();
+        assert!(!app.world.resource::<TriggerContacts>().contains(trigger, motor));
+
+        app.world.get_mut::<PlatformerMotor>(motor).unwrap().allow_step = false;
+        app.update();
+        assert!(app.world.resource::<TriggerContacts>().contains(trigger, motor));
+    }
+
+    fn get_motor() -> PlatformerMotor {
+        PlatformerMotor{
+            velocity:     Vec2::ZERO,
+            allow_step:   true,
+            allow_snap:   false,
+            drop_through: false,
+        }
+    }
+}